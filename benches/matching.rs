@@ -0,0 +1,124 @@
+//!
+//! Matching benchmarks (nightly: `cargo bench`).
+//!
+
+#![feature(test)]
+
+extern crate test;
+
+use avkeys::{matches, AvKey, AvKeyParameter, AvKeybind, Key, KeyCode, Registry};
+use test::Bencher;
+
+// Overridden with each of `keybinds()`, by whether they have a `{d}`.
+#[AvKeybind(Ctrl+A)]
+pub fn Bench(_state : &mut ()) {}
+
+#[AvKeybind(Ctrl+{d})]
+pub fn BenchDigit(_state : &mut (), _digit : usize) {}
+
+///
+/// Keybinds spread over every modifier combination,
+/// with the one being pressed registered last.
+/// 
+fn keybinds() -> Vec<Vec<AvKey>> {
+    let modifiers : [KeyCode; 4] = [29, 42, 56, 125];
+
+    let mut keybinds = (1..16u32)
+        .flat_map(|bits| {
+            let mods = modifiers.iter()
+                .enumerate()
+                .filter(move |(i, _)| bits & (1 << i) != 0)
+                .map(|(_, m)| AvKey::Key(*m))
+                .collect::<Vec<_>>();
+
+            (16..=25).map(AvKey::Key)
+                .chain(std::iter::once(AvKey::Parameter(AvKeyParameter::DigitKey)))
+                .map(move |k| {
+                    let mut keys = mods.clone();
+                    keys.push(k);
+                    keys
+                })
+        })
+        .collect::<Vec<_>>();
+
+    keybinds.push(vec![AvKey::Key(29), AvKey::Key(42), AvKey::Key(30)]);
+    keybinds
+}
+
+const PRESSED : [KeyCode; 3] = [29, 42, 30];
+
+#[bench]
+fn naive_matches(b : &mut Bencher) {
+    let keybinds = keybinds();
+
+    b.iter(|| {
+        keybinds.iter()
+            .find_map(|keys| matches(keys, test::black_box(&PRESSED)))
+    });
+}
+
+#[bench]
+fn registry_dispatch(b : &mut Bencher) {
+    let mut registry = Registry::new();
+    keybinds().into_iter()
+        .for_each(|keys| if keys.iter().any(|k| k.key_parameter().is_some()) {
+            registry.register(BenchDigit::with_keys(keys).unwrap());
+        } else {
+            registry.register(Bench::with_keys(keys).unwrap());
+        });
+
+    b.iter(|| registry.dispatch(test::black_box(&PRESSED), &mut ()));
+}
+
+///
+/// `AvKey` matching against a key parameter, for every keycode.
+/// 
+#[bench]
+fn parameter_matches(b : &mut Bencher) {
+    let param = AvKey::Parameter(AvKeyParameter::FunctionKey);
+
+    b.iter(|| {
+        (0..256).map(AvKey::Key)
+            .filter(|k| k.matches(&test::black_box(param)))
+            .count()
+    });
+}
+
+///
+/// The same, through a linear search of the parameter's keys.
+/// 
+#[bench]
+fn parameter_keys_contains(b : &mut Bencher) {
+    let param = AvKeyParameter::FunctionKey;
+
+    b.iter(|| {
+        (0..256)
+            .filter(|k| test::black_box(param).keys().contains(k))
+            .count()
+    });
+}
+
+///
+/// Every key name, for the lookup benchmarks.
+/// 
+fn names() -> Vec<&'static str> {
+    Key::ALL.iter().flat_map(|k| k.name().iter().copied()).collect()
+}
+
+///
+/// Name lookup through the sorted table's binary search.
+/// 
+#[bench]
+fn lookup_name_table(b : &mut Bencher) {
+    let names = names();
+    b.iter(|| names.iter().filter(|n| Key::lookup(*test::black_box(*n)).is_some()).count());
+}
+
+///
+/// The same, through `code_of`'s `match` over the names.
+/// 
+#[bench]
+fn lookup_name_match(b : &mut Bencher) {
+    let names = names();
+    b.iter(|| names.iter().filter(|n| Key::code_of(test::black_box(n)).is_some()).count());
+}
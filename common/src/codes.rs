@@ -1,5 +1,5 @@
 //!
-//! Collection of linux keycodes.
+//! Collection of linux keycodes (`KEY_*`) and buttons (`BTN_*`).
 //! 
 //! Auto generated by a macro from the C header file: 
 //! [/usr/include/linux/input-event-codes.h]
@@ -7,4 +7,19 @@
 
 use core_macros::keycodes;
 
-keycodes!();
\ No newline at end of file
+keycodes!(KEY, BTN);
+
+#[cfg(test)]
+mod tests {
+    use super::{BTN_LEFT, BTN_RIGHT, KEY_A, KEY_ESC, KEY_FN_F1, KEY_SPACE};
+
+    #[test]
+    fn linux_keycodes() {
+        assert_eq!(KEY_ESC, 1);
+        assert_eq!(KEY_A, 30);
+        assert_eq!(KEY_SPACE, 57);
+        assert_eq!(KEY_FN_F1, 0x1d2);
+        assert_eq!(BTN_LEFT, 0x110);
+        assert_eq!(BTN_RIGHT, 0x111);
+    }
+}
@@ -5,6 +5,7 @@
 //! 
 
 pub mod codes;
+pub mod modifiers;
 
 #[cfg(feature = "parsing")]
 pub mod parsed_key;
@@ -59,12 +60,69 @@ impl From<char> for AvKeyDiscrim<'_> {
     }
 }
 
+///
+/// When a keybind fires: as its keys are pressed (the default),
+/// or as they are released (e.g. push-to-talk).
+///
+/// Set with `#[AvKeybind(Logo+V, on = release)]`.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TriggerMode {
+    #[default]
+    Press,
+    Release,
+}
+
+///
+/// Conversion from a captured key parameter value (see `AvKeyParameter::value`)
+/// into the type declared by a keybind callback's argument.
+///
+/// ### Example
+/// ```ignore
+/// #[AvKeybind(Ctrl+{d})]
+/// pub fn SwitchTab(state : &mut (), tab : u8) { ... }
+/// ```
+///
+pub trait FromKeyParameter {
+    fn from_parameter(value: usize) -> Self;
+}
+
+macro_rules! from_key_parameter {
+    ($($ty:ty),*) => {
+        $(
+            impl FromKeyParameter for $ty {
+                fn from_parameter(value: usize) -> Self {
+                    value as $ty
+                }
+            }
+        )*
+    };
+}
+
+from_key_parameter!(usize, u8, u16, u32, u64, isize, i8, i16, i32, i64);
+
+///
+/// Characters captured by `{printable}`.
+///
+impl FromKeyParameter for char {
+    fn from_parameter(value: usize) -> Self {
+        char::from_u32(value as u32).unwrap_or(char::REPLACEMENT_CHARACTER)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::AvKeyDiscrim;
+    use crate::{AvKeyDiscrim, FromKeyParameter};
 
     #[test]
     fn avkey_discrim() {
         let a = AvKeyDiscrim::Str("sdfgh");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn from_key_parameter() {
+        assert_eq!(usize::from_parameter(5), 5);
+        assert_eq!(u8::from_parameter(12), 12);
+        assert_eq!(char::from_parameter('a' as usize), 'a');
+    }
+}
@@ -0,0 +1,93 @@
+//!
+//! Modifier keys (Ctrl, Shift, Alt, and Meta) shared by the
+//! macros' attribute checks and the library's matching.
+//!
+
+use crate::KeyCode;
+
+// Modifier Keys:                     LCtrl LShift RShift LAlt RCtrl RAlt LMeta RMeta
+pub const MODIFIER_KEYS : [KeyCode; 8] = [29,   42,    54,    56,  97,   100, 125,  126];
+
+// Modifier Sides:                               Ctrl       Shift      Alt         Meta
+pub const MODIFIER_SIDES : [(KeyCode, KeyCode); 4] = [(29, 97), (42, 54), (56, 100), (125, 126)];
+
+///
+/// Side-less modifier names, which stand for either side
+/// of their modifier (given by its left-side keycode), primary name first.
+///
+pub const EITHER_SIDE_NAMES : [(&str, KeyCode); 6] = [
+    ("Ctrl", 29), ("Shift", 42), ("Alt", 56), ("Meta", 125), ("Logo", 125), ("Win", 125),
+];
+
+///
+/// Names of the modifier keys and their aliases, side-less or not.
+///
+pub const MODIFIER_NAMES : [&str; 14] = [
+    "Ctrl", "LeftCtrl", "RightCtrl", "Shift", "LeftShift", "RightShift", "Alt",
+    "LeftAlt", "RightAlt", "Meta", "Logo", "Win", "LeftMeta", "RightMeta",
+];
+
+///
+/// Whether a key is a modifier key (Ctrl, Shift, Alt, or Meta &mdash; either side).
+///
+pub const fn is_modifier(key : KeyCode) -> bool {
+    let mut i = 0;
+    while i < MODIFIER_KEYS.len() {
+        if MODIFIER_KEYS[i] == key {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+///
+/// The same modifier on the other side of the keyboard, or `None` for other keys.
+///
+pub const fn other_side(key : KeyCode) -> Option<KeyCode> {
+    let mut i = 0;
+    while i < MODIFIER_SIDES.len() {
+        let (left, right) = MODIFIER_SIDES[i];
+        if key == left {
+            return Some(right);
+        }
+        if key == right {
+            return Some(left);
+        }
+        i += 1;
+    }
+    None
+}
+
+///
+/// Returns the modifier a side-less name (`Ctrl`) stands for, by its left-side keycode.
+///
+pub fn either_side_code(name : &str) -> Option<KeyCode> {
+    EITHER_SIDE_NAMES.iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, code)| *code)
+}
+
+///
+/// Returns the side-less name of a modifier (`Ctrl` for `29`), by its left-side keycode.
+///
+pub fn either_side_name(code : KeyCode) -> Option<&'static str> {
+    EITHER_SIDE_NAMES.iter()
+        .find(|(_, c)| *c == code)
+        .map(|(name, _)| *name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sides() {
+        assert_eq!(other_side(29), Some(97));
+        assert_eq!(other_side(100), Some(56));
+        assert_eq!(other_side(30), None);
+        assert_eq!(either_side_code("Win"), Some(125));
+        assert_eq!(either_side_name(125), Some("Meta"));
+        assert!(is_modifier(126) && !is_modifier(1));
+    }
+}
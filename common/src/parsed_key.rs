@@ -1,17 +1,21 @@
 use std::collections::HashMap;
+use std::str::FromStr;
 
 use lazy_static::lazy_static;
 use proc_macro2::TokenStream;
 use quote::{quote, ToTokens, quote_spanned};
 use syn::{
     braced, bracketed,
-    parse::{Parse, ParseBuffer},
+    parse::{Parse, ParseBuffer, Parser},
     punctuated::Punctuated,
     spanned::Spanned,
     token::{Brace, Bracket},
-    LitInt, Signature, Token, TypePath, LitChar,
+    LitInt, PatType, Signature, Token, TypePath, LitChar,
 };
 
+use crate::TriggerMode;
+use crate::modifiers::{either_side_code, is_modifier, MODIFIER_NAMES};
+
 lazy_static! {
     ///
     /// Key parameters and their short codes.
@@ -19,8 +23,10 @@ lazy_static! {
     pub static ref KEY_PARAMS: HashMap<&'static str, &'static str> = {
         HashMap::from_iter(
             [
-                ("d", "::avkeys_common::AvKeyParameter::DigitKey"),
-                ("f", "::avkeys_common::AvKeyParameter::FunctionKey"),
+                ("d", "::avkeys::AvKeyParameter::DigitKey"),
+                ("f", "::avkeys::AvKeyParameter::FunctionKey"),
+                ("a", "::avkeys::AvKeyParameter::LetterKey"),
+                ("printable", "::avkeys::AvKeyParameter::PrintableKey"),
             ]
             .into_iter(),
         )
@@ -28,6 +34,13 @@ lazy_static! {
 }
 
 
+///
+/// Escapes braces (`{d}`) in messages passed to `assert!` as its format string.
+///
+fn escape_braces(message: String) -> String {
+    message.replace('{', "{{").replace('}', "}}")
+}
+
 ///
 /// Possible types used to name a key.
 /// 
@@ -40,29 +53,45 @@ pub enum ParsedKeyDisc {
 ///
 /// AvKey that is being parsed.
 ///
-/// Can either be a Key Name, Key Code, or Key Parameter.
+/// Can either be a Key Name, Key Code, Key Parameter,
+/// or a negated (`!`) Key Name/Code.
 ///
 pub enum ParsedKey {
     Name(ParsedKeyDisc),
     Code(Bracket, LitInt),
     Parameter(Brace, syn::Ident),
+    NotHeld(Token![!], Box<ParsedKey>),
 }
 
 impl Parse for ParsedKey {
     fn parse(input: &ParseBuffer) -> syn::Result<Self> {
+        if input.peek(Token![!]) {
+            let bang: Token![!] = input.parse()?;
+            let key: ParsedKey = input.parse()?;
+
+            return match key {
+                ParsedKey::Name(_) | ParsedKey::Code(_, _) => Ok(Self::NotHeld(bang, Box::new(key))),
+                _ => Err(syn::Error::new(
+                    key.span(),
+                    "Only key names and codes can be negated (e.g. `!Shift`, `![42]`)",
+                )),
+            };
+        }
+
         if input.peek(syn::token::Bracket) {
             let inside;
             let brackets = bracketed!(inside in input);
-            return Ok(Self::Code(
-                brackets,
-                inside.parse().map_err(|err| {
+            let code : LitInt = inside.parse()
+                .and_then(|code : LitInt| code.base10_parse::<u32>().map(|_| code))
+                .map_err(|err| {
                     syn::Error::new(
                         err.span(),
-                        "Expected a key code here (any integer literal e.g. `11`, `124`)\n\
+                        "Expected a key code here (any integer literal e.g. `11`, `124`, `0x6f`)\n\
                     Full Example: `#[AvKeybind(Ctrl+Alt+[111])]`",
                     )
-                })?,
-            ));
+                })?;
+
+            return Ok(Self::Code(brackets, code));
         }
 
         if input.peek(syn::token::Brace) {
@@ -102,49 +131,144 @@ impl Parse for ParsedKey {
 
         Err(input.error(
             "Expected either a Name (`1`, `A`, `Delete`, or char escape: `'\\\\'`, `'+'`); Code (`[12]`, `[111]`); \
-            Key Parameter (`{d}`, `{f}`).\nFull Example: `#[AvKeybind(Ctrl+[111]+{f})]`",
+            Key Parameter (`{d}`, `{f}`); or a negated Name/Code (`!Shift`).\nFull Example: `#[AvKeybind(Ctrl+[111]+{f})]`",
         ))
     }
 }
 
 impl ParsedKey {
+    pub fn span(&self) -> proc_macro2::Span {
+        match self {
+            ParsedKey::Name(ParsedKeyDisc::Ident(ident)) => ident.span(),
+            ParsedKey::Name(ParsedKeyDisc::LitChar(ch)) => ch.span(),
+            ParsedKey::Name(ParsedKeyDisc::LitInt(int)) => int.span(),
+            ParsedKey::Code(b, _) => b.span,
+            ParsedKey::Parameter(b, _) => b.span,
+            ParsedKey::NotHeld(bang, key) => bang.span.join(key.span()).unwrap_or(bang.span),
+        }
+    }
+
+    ///
+    /// Whether this is a modifier key, by name or code
+    /// (never for key parameters or negated keys).
+    ///
+    pub fn is_modifier(&self) -> bool {
+        match self {
+            ParsedKey::Name(ParsedKeyDisc::Ident(ident)) => MODIFIER_NAMES.contains(&ident.to_string().as_str()),
+            ParsedKey::Code(_, int) => int.base10_parse().map_or(false, is_modifier),
+            _ => false,
+        }
+    }
+
+    ///
+    /// The key itself, without any negation.
+    ///
+    pub fn inner(&self) -> &ParsedKey {
+        match self {
+            ParsedKey::NotHeld(_, key) => key.inner(),
+            key => key,
+        }
+    }
+
+    ///
+    /// This key as written in the attribute: `Ctrl`, `'+'`, `[111]`, `{d}`, `!Shift`.
+    ///
+    pub fn to_display_string(&self) -> String {
+        match self {
+            ParsedKey::Name(ParsedKeyDisc::Ident(ident)) => ident.to_string(),
+            ParsedKey::Name(ParsedKeyDisc::LitChar(ch)) => ch.to_token_stream().to_string(),
+            ParsedKey::Name(ParsedKeyDisc::LitInt(int)) => int.to_string(),
+            ParsedKey::Code(_, int) => format!("[{int}]"),
+            ParsedKey::Parameter(_, ident) => format!("{{{ident}}}"),
+            ParsedKey::NotHeld(_, key) => format!("!{}", key.to_display_string()),
+        }
+    }
+
     pub fn to_lookup(&self) -> proc_macro2::TokenStream {
         match self {
+            ParsedKey::NotHeld(bang, key) => {
+                let s = bang.span;
+                let key = key.to_lookup();
+                quote_spanned! {
+                    s => match #key {
+                        ::avkeys::AvKey::Key(k) | ::avkeys::AvKey::EitherSide(k) => ::avkeys::AvKey::NotHeld(k),
+                        k => k,
+                    }
+                }
+            },
+            ParsedKey::Name(ParsedKeyDisc::Ident(ident)) if either_side_code(&ident.to_string()).is_some() => {
+                let s = ident.span();
+                quote_spanned! {
+                    s => ::avkeys::AvKey::EitherSide(::avkeys::Key::#ident.code())
+                }
+            },
             ParsedKey::Name(ParsedKeyDisc::Ident(ident)) => {
                 let s = ident.span();
                 quote_spanned! {
-                    s => ::avkeys_common::AvKey::Key(Key::#ident.into())
+                    s => ::avkeys::AvKey::Key(::avkeys::Key::#ident.code())
                 }
             },
             ParsedKey::Name(ParsedKeyDisc::LitChar(ch)) => {
                 let s = ch.span();
                 let err_text = format!("Could not find `'{}'` in key aliases list.", ch.value());
+                // `Key::code_of` takes a `&str` and, unlike `Key::lookup_const`,
+                // needs no const trait to be called in `DEFAULT_KEYS`.
+                let name = syn::LitStr::new(&ch.value().to_string(), s);
                 quote_spanned! {
-                    s => ::avkeys_common::AvKey::Key(Key::lookup_const(#ch).expect(#err_text).into())
+                    s => ::avkeys::AvKey::Key(match ::avkeys::Key::code_of(#name) { Some(c) => c, None => panic!(#err_text) })
                 }
             },
             ParsedKey::Name(ParsedKeyDisc::LitInt(int)) => {
+                // A bare number names a digit key (`1` is `Digit1`), not a keycode,
+                // so look it up through its char alias.
                 let s = int.span();
+                let ch = syn::LitChar::new(
+                    int.base10_digits().chars().next().unwrap_or('0'),
+                    s,
+                );
                 let err_text = format!("Could not find `{}` in key aliases list.", int.to_string());
+                let name = syn::LitStr::new(&ch.value().to_string(), s);
                 quote_spanned! {
-                    s => ::avkeys_common::AvKey::Key(Key::lookup_const(#int).expect(#err_text).into())
+                    s => ::avkeys::AvKey::Key(match ::avkeys::Key::code_of(#name) { Some(c) => c, None => panic!(#err_text) })
                 }
             },
             ParsedKey::Code(_, int) => {
+                // Written out in decimal without any suffix, so hex
+                // (`[0x6f]`) and suffixed (`[111u16]`) codes are plain `KeyCode`s.
                 let s = int.span();
+                let code = LitInt::new(int.base10_digits(), s);
                 quote_spanned! {
-                    s => ::avkeys_common::AvKey::Key(#int)
+                    s => ::avkeys::AvKey::Key(#code)
                 }
             },
-            ParsedKey::Parameter(b, ident) => {
+            ParsedKey::Parameter(b, _) => {
                 let s = b.span;
-                let path = KEY_PARAMS.get(ident.to_string().as_str()).unwrap();
-                let path : syn::Path = syn::parse_str(path).unwrap();
-
-                quote_spanned! { s => ::avkeys_common::AvKey::Parameter(#path) }
+                let param = self.to_parameter_lookup();
+                quote_spanned! { s => ::avkeys::AvKey::Parameter(#param) }
             },
         }.into_token_stream()
     }
+
+    ///
+    /// The `AvKeyParameter` expression of a key parameter,
+    /// or `None` for any other key.
+    ///
+    pub fn to_parameter_lookup(&self) -> Option<proc_macro2::TokenStream> {
+        let ParsedKey::Parameter(b, ident) = self else {
+            return None;
+        };
+
+        let s = b.span;
+        Some(match KEY_PARAMS.get(ident.to_string().as_str()) {
+            Some(path) => {
+                let path : syn::Path = syn::parse_str(path).unwrap();
+                quote_spanned! { s => #path }
+            },
+            // Custom key parameters (see `keyparameters!`) are constants
+            // named after their short code, which must be in scope.
+            None => quote_spanned! { s => #ident },
+        })
+    }
 }
 ///
 /// Parsed macro representation of AvKeybind.
@@ -160,38 +284,205 @@ impl ParsedKeybind {
         self.0.into_iter()
     }
 
-    pub fn validate_parameter_names(&self) -> Option<TokenStream> {
-        let mut possible_parameter_errors = self
-            .iter()
-            .filter(|k| matches!(k, ParsedKey::Parameter(_, _)))
-            .filter_map(|k| match k {
-                ParsedKey::Parameter(_, ident) => {
-                    let p_type = ident.to_string();
-                    if KEY_PARAMS.get(&p_type.as_str()).is_none() {
-                        // No recognised key paramater by that identifier.
-                        Some(syn::Error::new(
-                            ident.span(),
-                            format!(
-                                "Unknown key parameter '{p_type}'.\nExpected one of: {}",
-                                KEY_PARAMS
-                                    .keys()
-                                    .map(|k| format!("`{}`, ", k))
-                                    .collect::<String>()
-                            ),
-                        ))
-                    } else {
-                        None
-                    }
+    ///
+    /// The combination as written in the attribute,
+    /// without whitespace: `Ctrl+Alt+[111]`.
+    ///
+    pub fn to_display_string(&self) -> String {
+        self.iter()
+            .map(ParsedKey::to_display_string)
+            .collect::<Vec<_>>()
+            .join("+")
+    }
+
+    ///
+    /// The [combination](ParsedKeybind::to_display_string), with
+    /// the `index`-th key underlined on the line below, for error messages.
+    ///
+    fn highlight(&self, index: usize) -> String {
+        let keys = self.iter()
+            .map(ParsedKey::to_display_string)
+            .collect::<Vec<_>>();
+
+        let before = keys[..index].iter().map(|k| k.chars().count() + 1).sum::<usize>();
+        let width = keys[index].chars().count();
+
+        format!("{}\n{}{}", keys.join("+"), " ".repeat(before), "^".repeat(width))
+    }
+
+    ///
+    /// Ensures bare numbers are single digits (`Ctrl+1`),
+    /// suggesting the `[code]` syntax for anything else.
+    ///
+    pub fn validate_key_names(&self) -> Option<TokenStream> {
+        self.key_name_errors()
+            .map(|e| e.into_compile_error().into())
+    }
+
+    fn key_name_errors(&self) -> Option<syn::Error> {
+        let mut errors = self.iter().map(ParsedKey::inner).enumerate().filter_map(|(i, k)| match k {
+            ParsedKey::Name(ParsedKeyDisc::LitInt(int))
+                if int.suffix().is_empty() && int.base10_digits().len() == 1 => None,
+            ParsedKey::Name(ParsedKeyDisc::LitInt(int)) => Some(syn::Error::new(
+                int.span(),
+                format!(
+                    "`{int}` is not a valid key name; for a raw keycode use `[{}]`\n\n{}",
+                    int.base10_digits(),
+                    self.highlight(i),
+                ),
+            )),
+            _ => None,
+        }).chain(self.duplicate_key_errors());
+
+        let e = errors.next();
+        e.map(|mut e| {
+            errors.for_each(|err| e.extend(err));
+            e
+        })
+    }
+
+    ///
+    /// Errors for keys written more than once (`Ctrl+Ctrl+A`, `{d}+{d}`),
+    /// or both pressed and not held (`Ctrl+A+!Ctrl`), pointing at each repeat.
+    ///
+    /// Keys are compared as written, except raw codes which are compared
+    /// by value (`[29]` and `[0x1d]`); different names for the same key
+    /// (`Esc+Escape`) are left to [ParsedKeybind::resolved_key_checks].
+    ///
+    fn duplicate_key_errors(&self) -> impl Iterator<Item = syn::Error> + '_ {
+        let identity = |key : &ParsedKey| match key.inner() {
+            ParsedKey::Code(_, int) => format!("[{}]", int.base10_digits()),
+            key => key.to_display_string(),
+        };
+
+        let keys = self.iter()
+            .map(|k| (matches!(k, ParsedKey::NotHeld(..)), identity(k)))
+            .collect::<Vec<_>>();
+
+        self.iter().enumerate()
+            .filter_map(move |(i, key)| {
+                let (not_held, name) = &keys[i];
+                let earlier = keys[..i].iter().position(|(_, n)| n == name)?;
+
+                let message = match keys[earlier].0 == *not_held {
+                    true => format!("`{}` appears more than once in this keybind", key.to_display_string()),
+                    false => self.contradiction(earlier, i),
+                };
+
+                Some(syn::Error::new(key.span(), format!("{message}\n\n{}", self.highlight(i))))
+            })
+    }
+
+    ///
+    /// Message for a key both pressed and not held, at `earlier` and `later`.
+    ///
+    fn contradiction(&self, earlier : usize, later : usize) -> String {
+        let (a, b) = (&self.0[earlier], &self.0[later]);
+        let (pressed, not_held) = match a {
+            ParsedKey::NotHeld(..) => (b, a),
+            _ => (a, b),
+        };
+
+        format!(
+            "`{}` is both pressed and not held (`{}`) in this keybind",
+            pressed.to_display_string(),
+            not_held.to_display_string(),
+        )
+    }
+
+    ///
+    /// `const` assertions that no two keys of this keybind stand for the same
+    /// physical key once their names are resolved (`Esc+Escape`, `A+[30]`,
+    /// `'1'+Digit1`, `Alt+LeftAlt`), nor a pressed key is also not held (`Alt+Tab+!LeftAlt`).
+    ///
+    /// Key names are only resolved by the `avkeys` crate, so these are checked
+    /// at compile time against `keys`, the constant holding the resolved keys
+    /// (see `AvKey::overlaps`), failing with a message pointing at the later key.
+    ///
+    pub fn resolved_key_checks(&self, keys: &syn::Ident) -> TokenStream {
+        let checks = self.iter().enumerate()
+            .flat_map(|(j, b)| (0..j).map(move |i| (i, j, b)))
+            .filter(|(i, _, b)| !matches!(self.0[*i].inner(), ParsedKey::Parameter(..)) && !matches!(b.inner(), ParsedKey::Parameter(..)))
+            .map(|(i, j, b)| {
+                let a = &self.0[i];
+                let message = match matches!(a, ParsedKey::NotHeld(..)) == matches!(b, ParsedKey::NotHeld(..)) {
+                    true => format!(
+                        "`{}` is the same key as `{}` in this keybind",
+                        b.to_display_string(),
+                        a.to_display_string(),
+                    ),
+                    false => self.contradiction(i, j),
+                };
+                let message = escape_braces(format!("{message}\n\n{}", self.highlight(j)));
+
+                quote_spanned! {
+                    b.span() => const _ : () = assert!(!#keys[#i].overlaps(&#keys[#j]), #message);
                 }
-                _ => None,
             });
 
-        let e = possible_parameter_errors.next();
+        quote! { #(#checks)* }
+    }
+
+    ///
+    /// Errors for key parameters which are neither built in (`{d}`, ...) nor
+    /// one of the `custom` ones (declared with `keyparameters!`, see
+    /// [ParsedKeybindOptions::params]), listing the valid ones.
+    ///
+    pub fn parameter_name_errors(&self, custom: &[syn::Ident]) -> Option<syn::Error> {
+        let mut known = KEY_PARAMS.keys().map(|p| p.to_string()).collect::<Vec<_>>();
+        known.sort();
+        known.extend(custom.iter().map(|p| p.to_string()));
+
+        let expected = known.iter()
+            .map(|p| format!("`{{{p}}}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let mut errors = self.iter().enumerate().filter_map(|(i, k)| match k {
+            ParsedKey::Parameter(_, ident) if !known.contains(&ident.to_string()) => Some(syn::Error::new(
+                ident.span(),
+                format!(
+                    "Unknown key parameter `{{{ident}}}`.\nExpected one of: {expected}, \
+                        or a custom one listed in `params = [...]`\n\n{}",
+                    self.highlight(i),
+                ),
+            )),
+            _ => None,
+        });
+
+        let e = errors.next();
         e.map(|mut e| {
-            possible_parameter_errors.for_each(|err| e.extend(err));
+            errors.for_each(|err| e.extend(err));
             e
         })
-        .map(|e| e.into_compile_error().into())
+    }
+
+    ///
+    /// Key parameters written before a fixed non-modifier key (`{d}+A`),
+    /// for the key order lint: they read better last (`A+{d}`).
+    ///
+    pub fn misplaced_parameters(&self) -> impl Iterator<Item = &ParsedKey> + '_ {
+        let last_fixed = self.0.iter()
+            .rposition(|k| matches!(k, ParsedKey::Name(_) | ParsedKey::Code(..)) && !k.is_modifier());
+
+        self.iter()
+            .take(last_fixed.unwrap_or(0))
+            .filter(|k| matches!(k, ParsedKey::Parameter(..)))
+    }
+
+    ///
+    /// This combination with its key parameters moved last, as
+    /// suggested by the key order lint (see [ParsedKeybind::misplaced_parameters]).
+    ///
+    pub fn to_ordered_display_string(&self) -> String {
+        let (params, keys) = self.iter()
+            .partition::<Vec<_>, _>(|k| matches!(k, ParsedKey::Parameter(..)));
+
+        keys.into_iter()
+            .chain(params)
+            .map(ParsedKey::to_display_string)
+            .collect::<Vec<_>>()
+            .join("+")
     }
 
     pub fn parameters_present(&self) -> impl Iterator<Item = String> + '_ {
@@ -201,50 +492,49 @@ impl ParsedKeybind {
         })
     }
 
-    pub fn key_parameter_types_delcared_in_fn<'a>(
+    ///
+    /// Arguments of the callback bound to key parameters:
+    /// every argument after the first (`state`) one.
+    ///
+    pub fn key_parameter_args_declared_in_fn<'a>(
         &self,
         sig: &'a Signature,
-    ) -> impl Iterator<Item = &'a TypePath> + 'a {
-        sig.inputs.iter().filter_map(|param| match param {
+    ) -> impl Iterator<Item = &'a PatType> + 'a {
+        sig.inputs.iter().skip(1).filter_map(|param| match param {
             syn::FnArg::Receiver(_) => None,
-            syn::FnArg::Typed(ty) => match &*ty.ty {
-                syn::Type::Path(p) => Some(p),
-                _ => None,
-            },
+            syn::FnArg::Typed(ty) => Some(ty),
         })
     }
 
+    ///
+    /// Generates `let <arg> : <Type> = ...;` bindings for each key parameter argument,
+    /// converting the captured value through `FromKeyParameter`.
+    ///
     pub fn generate_key_parameter_assignments<'a>(
         &self,
         sig: &'a Signature,
     ) -> Result<TokenStream, TokenStream> {
-        let v = sig
-            .inputs
-            .iter()
-            .filter_map(|param| match param {
-                syn::FnArg::Receiver(_) => None,
-                syn::FnArg::Typed(ty) => match &*ty.ty {
-                    syn::Type::Path(p) => Some((ty, p)),
-                    _ => None,
-                },
-            })
+        let v = self
+            .key_parameter_args_declared_in_fn(sig)
             .collect::<Vec<_>>();
 
         if v.len() == 0 {
             return Ok(quote! {}.into());
         }
 
-        if v.len() > self.parameters_present().count() {
-            if self.parameters_present().count() == 0 {
+        let count = self.parameters_present().count();
+
+        if v.len() > count {
+            if count == 0 {
                 return Err(
-                    syn::Error::new(v[0].0.span(), "Unexpected extra function parameters.\nDid you forget to specify key parameters in #[AvKeybind(...)]?")
+                    syn::Error::new(v[0].span(), "Unexpected extra function parameters.\nDid you forget to specify key parameters in #[AvKeybind(...)]?")
                         .into_compile_error().into()
                 );
             }
 
-            let mut iter = (&v[(v.len() - self.parameters_present().count())..])
+            let mut iter = (&v[count..])
                 .iter()
-                .map(|(p, _)| {
+                .map(|p| {
                     syn::Error::new(
                         p.pat.span(),
                         "Excess key parameter defined here.\nPlease remove it.",
@@ -262,8 +552,8 @@ impl ParsedKeybind {
                 .into());
         }
 
-        let iter_v = v.iter().enumerate().map(|(_, (arg, _))| match &*arg.pat {
-            syn::Pat::Ident(ident) => Ok(ident),
+        let iter_v = v.iter().map(|arg| match &*arg.pat {
+            syn::Pat::Ident(ident) => Ok((ident, &arg.ty)),
             _ => Err(syn::Error::new(
                 arg.pat.span(),
                 "Expected identifier for key parameter name, try `key_param1` instead.",
@@ -285,11 +575,12 @@ impl ParsedKeybind {
             return Err(err.into_compile_error().into());
         }
 
-        let iter_v = iter_v.filter_map(Result::ok).enumerate().map(|(i, a)| {
+        let iter_v = iter_v.filter_map(Result::ok).enumerate().map(|(i, (a, ty))| {
             let attrs = a.attrs.iter();
-            quote! {
-                #(#attrs)*
-                let #a = __params__[#i];
+            let s = ty.span();
+            quote_spanned! {
+                s => #(#attrs)*
+                let #a : #ty = ::avkeys_common::FromKeyParameter::from_parameter(__params__[#i]);
             }
         });
 
@@ -299,9 +590,14 @@ impl ParsedKeybind {
         .into())
     }
 
+    ///
+    /// Ensures there is one callback argument for each key parameter,
+    /// and that it declares a real type (`times : usize`), rather than
+    /// the key parameter's short code (`times : d`).
+    ///
     pub fn validate_func_sign_against_key_params(&self, sig: &Signature) -> Option<TokenStream> {
         let params = self
-            .key_parameter_types_delcared_in_fn(sig)
+            .key_parameter_args_declared_in_fn(sig)
             .collect::<Vec<_>>();
 
         let results = self.parameters_present()
@@ -309,27 +605,26 @@ impl ParsedKeybind {
             .map(
                 |(i, declared_param)|
                     params.get(i)
-                        .and_then(|param_in_fn| 
-                            param_in_fn.path.get_ident()
-                        )
-                        .map(|param| {
-                            if param.to_string() == declared_param {
-                                Ok(param)
-                            } else {
-                                Err(syn::Error::new(
-                                    param.span(), 
+                        .map(|param| match &*param.ty {
+                            syn::Type::Path(TypePath { qself: None, path })
+                                if path.get_ident()
+                                    .map(|ident| KEY_PARAMS.contains_key(ident.to_string().as_str()))
+                                    .unwrap_or(false) => Err(syn::Error::new(
+                                    param.ty.span(),
                                     format!(
-                                        "Expected key parameter `{declared_param}` here, got `{}`",
-                                        param.to_string()
+                                        "Key parameters are bound by position, not by type: `{}` is not a type.\n\
+                                        Declare the captured value's type instead, e.g. `{} : usize`",
+                                        path.get_ident().unwrap(),
+                                        param.pat.to_token_stream(),
                                     )
-                                ))
-                            }
+                                )),
+                            _ => Ok(param),
                         })
                         .unwrap_or(Err(
                             syn::Error::new(
                                 sig.inputs.span(),
-                                format!("Expected key parameter `{declared_param}` in function delcaration.\n\
-                                Append `key_param{} : {declared_param}` to the end of the parameter list.", i + 1)
+                                format!("Expected an argument for key parameter `{{{declared_param}}}` in function delcaration.\n\
+                                Append `key_param{} : usize` to the end of the parameter list.", i + 1)
                             )
                         ))
             ).collect::<Vec<_>>();
@@ -352,6 +647,413 @@ impl ParsedKeybind {
     }
 }
 
+impl ParsedKeybind {
+    ///
+    /// Parses and validates the keys of an `#[AvKeybind(...)]` attribute,
+    /// as the macro does.
+    ///
+    /// This needs no proc-macro context, so it can also be used
+    /// from build scripts and codegen tools: see also the [FromStr] impl.
+    ///
+    /// ### Example
+    /// ```ignore
+    /// let keybind = ParsedKeybind::parse_tokens(quote! { Ctrl+[111]+{d} })?;
+    /// ```
+    ///
+    pub fn parse_tokens(tokens: TokenStream) -> syn::Result<Self> {
+        let keybind = Self::parse_keys(tokens)?;
+
+        if let Some(err) = keybind.parameter_name_errors(&[]) {
+            return Err(err);
+        }
+
+        Ok(keybind)
+    }
+
+    ///
+    /// Like [ParsedKeybind::parse_tokens], leaving key parameter names
+    /// to be checked once any custom ones are known.
+    ///
+    fn parse_keys(tokens: TokenStream) -> syn::Result<Self> {
+        let keybind: Self = syn::parse2(tokens).map_err(|err| {
+            syn::Error::new(
+                err.span(),
+                "Expected a + seperated non-trailing list of keys here:\n\
+                    Full Example: #[AvKeybind(Ctrl+[111]+{d})]",
+            )
+        })?;
+
+        if let Some(err) = keybind.key_name_errors() {
+            return Err(err);
+        }
+
+        Ok(keybind)
+    }
+}
+
+///
+/// Options after the keys of an `#[AvKeybind(Ctrl+A, exclusive)]` attribute.
+///
+#[derive(Default)]
+pub struct ParsedKeybindOptions {
+    ///
+    /// Only fire when exactly this keybind's modifiers are held.
+    ///
+    pub exclusive: bool,
+
+    ///
+    /// Type of the state passed to the callback (`state = MyWmState`),
+    /// `()` if not given.
+    ///
+    pub state: Option<syn::Type>,
+
+    ///
+    /// When the keybind fires (`on = press` or `on = release`),
+    /// on press if not given.
+    ///
+    pub trigger: TriggerMode,
+
+    ///
+    /// Capabilities the keybind needs to run
+    /// (`requires = ["screenshot", "media"]`), none if not given.
+    ///
+    pub requires: Vec<syn::LitStr>,
+
+    ///
+    /// Custom key parameters (declared with `keyparameters!`)
+    /// the keys use (`params = [kp]`), none if not given.
+    ///
+    pub params: Vec<syn::Ident>,
+
+    ///
+    /// How long the keys must be held for the keybind to fire,
+    /// in milliseconds (`hold_ms = 2000`, or the `Power hold 2s` suffix), if at all.
+    ///
+    pub hold_ms: Option<u64>,
+
+    ///
+    /// For sequence keybinds (`Ctrl+K then Ctrl+C`), the combinations
+    /// after the first, in order; empty for ordinary keybinds.
+    ///
+    pub then: Vec<ParsedKeybind>,
+
+    ///
+    /// The modifier whose release ends a repeated-chord interaction
+    /// (`anchor = Alt` for Alt-Tab), if any.
+    ///
+    pub anchor: Option<ParsedKey>,
+
+    ///
+    /// Silences the key order lint (`any_order`), which warns
+    /// about key parameters written before fixed keys.
+    ///
+    pub any_order: bool,
+}
+
+///
+/// A single option: a flag (`exclusive`) or a value (`state = MyWmState`).
+///
+struct ParsedKeybindOption(syn::Ident, Option<ParsedKeybindOptionValue>);
+
+///
+/// Value of an option: a list of strings (`["screenshot"]`), an integer
+/// (`2000`), or else a type (`MyWmState`, which also covers plain words like `release`).
+///
+enum ParsedKeybindOptionValue {
+    Strings(Bracket, Punctuated<syn::LitStr, Token![,]>),
+    Int(syn::LitInt),
+    Type(syn::Type),
+    Tokens(TokenStream),
+}
+
+impl Parse for ParsedKeybindOption {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        let value = match input.peek(Token![=]) {
+            true => {
+                input.parse::<Token![=]>()?;
+                Some(input.parse()?)
+            }
+            false => None,
+        };
+
+        Ok(Self(name, value))
+    }
+}
+
+impl Parse for ParsedKeybindOptionValue {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let strings = |input: syn::parse::ParseStream| {
+            let content;
+            Ok(Self::Strings(
+                bracketed!(content in input),
+                Punctuated::parse_terminated(&content)?,
+            ))
+        };
+
+        if input.peek(syn::LitInt) {
+            return Ok(Self::Int(input.parse()?));
+        }
+
+        // Array types (`[u8; 4]`) also start with a bracket.
+        let fork = input.fork();
+        if strings(&fork).is_ok() {
+            return strings(input);
+        }
+
+        if input.fork().parse::<syn::Type>().is_ok() {
+            return Ok(Self::Type(input.parse()?));
+        }
+
+        // Anything else (e.g. a key code `[56]` or char `'+'`), up to the next option.
+        input.step(|cursor| {
+            let mut tokens = TokenStream::new();
+            let mut rest = *cursor;
+            while let Some((tt, next)) = rest.token_tree() {
+                if matches!(&tt, proc_macro2::TokenTree::Punct(p) if p.as_char() == ',') {
+                    break;
+                }
+                tokens.extend([tt]);
+                rest = next;
+            }
+            Ok((Self::Tokens(tokens), rest))
+        })
+    }
+}
+
+impl ToTokens for ParsedKeybindOptionValue {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match self {
+            Self::Strings(bracket, strings) => bracket.surround(tokens, |t| strings.to_tokens(t)),
+            Self::Int(int) => int.to_tokens(tokens),
+            Self::Type(ty) => ty.to_tokens(tokens),
+            Self::Tokens(t) => t.to_tokens(tokens),
+        }
+    }
+}
+
+impl ParsedKeybindOptions {
+    pub const NAMES: [&'static str; 8] = [
+        "exclusive", "state = <Type>", "on = press | release", "requires = [\"<capability>\", ...]", "hold_ms = <milliseconds>",
+        "anchor = <Key>", "any_order", "params = [<code>, ...]",
+    ];
+
+    ///
+    /// Parses and validates a whole `#[AvKeybind(...)]` attribute:
+    /// the keys, then any comma-separated options.
+    ///
+    pub fn parse_attr(tokens: TokenStream) -> syn::Result<(ParsedKeybind, Self)> {
+        let mut tokens = tokens.into_iter();
+        let keys = tokens.by_ref()
+            .take_while(|t| !matches!(t, proc_macro2::TokenTree::Punct(p) if p.as_char() == ','))
+            .collect::<Vec<_>>();
+
+        // A trailing `hold <duration>` (`Logo hold 500ms`) isn't part of the keys.
+        let (keys, hold_ms) = match keys.iter().position(|t| matches!(t, proc_macro2::TokenTree::Ident(i) if i == "hold")) {
+            Some(i) => (&keys[..i], Some(Self::parse_hold(&keys[i], &keys[i + 1..])?)),
+            None => (&keys[..], None),
+        };
+
+        // `then` (never a key name) separates the steps of a sequence;
+        // `+` joins keys within a step, and `,` starts the options.
+        let mut steps = keys
+            .split(|t| matches!(t, proc_macro2::TokenTree::Ident(i) if i == "then"))
+            .map(|step| ParsedKeybind::parse_keys(step.iter().cloned().collect()))
+            .collect::<syn::Result<Vec<_>>>()?
+            .into_iter();
+
+        let keybind = steps.next().expect("`split` always yields a step");
+        let then = steps.collect::<Vec<_>>();
+
+        if let Some(key) = then.iter().flat_map(ParsedKeybind::iter).find(|k| matches!(k, ParsedKey::Parameter(..))) {
+            return Err(syn::Error::new(
+                key.span(),
+                "Key parameters are only supported in the first combination of a sequence",
+            ));
+        }
+
+        let parsed = Punctuated::<ParsedKeybindOption, Token![,]>::parse_terminated
+            .parse2(tokens.collect())?;
+
+        let mut options = Self { then, hold_ms, ..Self::default() };
+        for ParsedKeybindOption(name, value) in parsed {
+            match (name.to_string().as_str(), value) {
+                ("exclusive", None) => options.exclusive = true,
+                ("any_order", None) => options.any_order = true,
+                ("state", Some(ParsedKeybindOptionValue::Type(ty))) => options.state = Some(ty),
+                ("requires", Some(ParsedKeybindOptionValue::Strings(_, strings))) => {
+                    options.requires = strings.into_iter().collect();
+                },
+                ("requires", Some(value)) => return Err(syn::Error::new_spanned(
+                    quote! { #name = #value },
+                    "Expected a list of capabilities, e.g. `requires = [\"screenshot\"]`",
+                )),
+                ("params", Some(value)) => {
+                    let codes = |input: syn::parse::ParseStream| {
+                        let content;
+                        bracketed!(content in input);
+                        Punctuated::<syn::Ident, Token![,]>::parse_terminated(&content)
+                    };
+
+                    options.params = codes.parse2(value.to_token_stream())
+                        .map_err(|_| syn::Error::new_spanned(
+                            quote! { #name = #value },
+                            "Expected a list of custom key parameters, e.g. `params = [kp]`",
+                        ))?
+                        .into_iter()
+                        .collect();
+                },
+                ("hold_ms", Some(_)) if options.hold_ms.is_some() => return Err(syn::Error::new(
+                    name.span(),
+                    "The hold duration is already given by the `hold` suffix",
+                )),
+                ("hold_ms", Some(ParsedKeybindOptionValue::Int(ms))) => options.hold_ms = Some(ms.base10_parse()?),
+                ("hold_ms", Some(value)) => return Err(syn::Error::new_spanned(
+                    quote! { #name = #value },
+                    "Expected a duration in milliseconds, e.g. `hold_ms = 2000`",
+                )),
+                ("anchor", Some(value)) => {
+                    let key = syn::parse2::<ParsedKey>(value.to_token_stream())
+                        .ok()
+                        .filter(|k| matches!(k, ParsedKey::Name(_) | ParsedKey::Code(..)))
+                        .ok_or_else(|| syn::Error::new_spanned(
+                            quote! { #name = #value },
+                            "Expected a key name or code, e.g. `anchor = Alt`",
+                        ))?;
+
+                    // Whether it is one of the keybind's keys is checked once
+                    // key names are resolved (see `ParsedKeybindOptions::anchor_check`).
+                    options.anchor = Some(key);
+                },
+                ("on", Some(mode)) => {
+                    options.trigger = match mode.to_token_stream().to_string().as_str() {
+                        "press" => TriggerMode::Press,
+                        "release" => TriggerMode::Release,
+                        other => return Err(syn::Error::new_spanned(
+                            quote! { #name = #mode },
+                            format!("Unknown trigger mode `{other}`.\nExpected `on = press` or `on = release`"),
+                        )),
+                    }
+                },
+                (other, _) => return Err(syn::Error::new(
+                    name.span(),
+                    format!(
+                        "Unknown keybind option `{other}`.\nExpected one of: {}",
+                        Self::NAMES.map(|n| format!("`{n}`")).join(", ")
+                    ),
+                )),
+            }
+        }
+
+        if let Some(err) = keybind.parameter_name_errors(&options.params) {
+            return Err(err);
+        }
+
+        Ok((keybind, options))
+    }
+
+    ///
+    /// Parses the duration after a `hold` suffix (`500ms`, `2s`), in milliseconds.
+    ///
+    fn parse_hold(hold: &proc_macro2::TokenTree, rest: &[proc_macro2::TokenTree]) -> syn::Result<u64> {
+        const EXPECTED: &str = "Expected a duration after `hold`, in `ms` or `s`, e.g. `Logo hold 500ms`";
+
+        let int = match rest {
+            [proc_macro2::TokenTree::Literal(lit)] => match syn::Lit::new(lit.clone()) {
+                syn::Lit::Int(int) => int,
+                lit => return Err(syn::Error::new(lit.span(), EXPECTED)),
+            },
+            [] => return Err(syn::Error::new(hold.span(), EXPECTED)),
+            [first, ..] => return Err(syn::Error::new(first.span(), EXPECTED)),
+        };
+
+        let value = int.base10_parse::<u64>()?;
+        match int.suffix() {
+            "ms" => Ok(value),
+            "s" => value.checked_mul(1000)
+                .ok_or_else(|| syn::Error::new(int.span(), "Hold duration is too long")),
+            _ => Err(syn::Error::new(int.span(), EXPECTED)),
+        }
+    }
+
+    ///
+    /// A `const` assertion that the anchor is one of the keybind's keys once key
+    /// names are resolved, either side modifiers covering both sides
+    /// (`Alt+Tab, anchor = [56]`, `Escape+Tab, anchor = Esc`), against `keys`,
+    /// the constant holding the resolved keys (see `AvKey::overlaps`).
+    ///
+    /// Nothing when there is no anchor, or it is written the same as one of the keys.
+    ///
+    pub fn anchor_check(&self, keybind: &ParsedKeybind, keys: &syn::Ident) -> TokenStream {
+        let anchor = match &self.anchor {
+            Some(anchor) if keybind.iter().all(|k| k.to_display_string() != anchor.to_display_string()) => anchor,
+            _ => return TokenStream::new(),
+        };
+
+        let pressed = keybind.iter()
+            .enumerate()
+            .filter(|(_, k)| matches!(k, ParsedKey::Name(_) | ParsedKey::Code(..)))
+            .map(|(i, _)| i);
+
+        let message = escape_braces(format!(
+            "The anchor `{}` must be one of this keybind's keys: `{}`",
+            anchor.to_display_string(),
+            keybind.to_display_string(),
+        ));
+        let lookup = anchor.to_lookup();
+
+        quote_spanned! {
+            anchor.span() => const _ : () = assert!(false #(|| #keys[#pressed].overlaps(&#lookup))*, #message);
+        }
+    }
+
+    ///
+    /// The state type, defaulting to `()`.
+    ///
+    pub fn state_type(&self) -> syn::Type {
+        self.state.clone().unwrap_or_else(|| syn::parse_quote! { () })
+    }
+
+    ///
+    /// Ensures the callback's first argument is `&mut <state type>`.
+    ///
+    pub fn validate_state_arg(&self, sig: &Signature) -> Option<TokenStream> {
+        let arg = match sig.inputs.first()? {
+            syn::FnArg::Typed(arg) => arg,
+            syn::FnArg::Receiver(_) => return None,
+        };
+
+        let state = self.state_type();
+        if arg.ty.to_token_stream().to_string() == quote! { &mut #state }.to_string() {
+            return None;
+        }
+
+        let expected = format!("&mut {}", state.to_token_stream());
+
+        Some(
+            syn::Error::new(
+                arg.ty.span(),
+                format!(
+                    "Expected the state argument to be `{expected}`{}",
+                    match self.state {
+                        Some(_) => ", as declared with `state = ...`".to_string(),
+                        None => ".\nFor another state type, declare it: `#[AvKeybind(..., state = MyState)]`".to_string(),
+                    }
+                ),
+            )
+            .into_compile_error(),
+        )
+    }
+}
+
+impl FromStr for ParsedKeybind {
+    type Err = syn::Error;
+
+    fn from_str(s: &str) -> syn::Result<Self> {
+        Self::parse_tokens(syn::parse_str(s)?)
+    }
+}
+
 impl Parse for ParsedKeybind {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         Ok(Self(
@@ -359,3 +1061,267 @@ impl Parse for ParsedKeybind {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use quote::ToTokens;
+
+    use crate::TriggerMode;
+
+    use super::{ParsedKey, ParsedKeyDisc, ParsedKeybind, ParsedKeybindOptions};
+
+    #[test]
+    fn parse_plus_word() {
+        let keybind : ParsedKeybind = syn::parse_str("Ctrl+Plus").unwrap();
+        let names = keybind.iter()
+            .map(|k| match k {
+                ParsedKey::Name(ParsedKeyDisc::Ident(i)) => i.to_string(),
+                _ => panic!("Expected key names only"),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, ["Ctrl", "Plus"]);
+    }
+
+    #[test]
+    fn display_string() {
+        let keybind : ParsedKeybind = "Ctrl + '+' + [111] + {d} + !Shift".parse().unwrap();
+        assert_eq!(keybind.to_display_string(), "Ctrl+'+'+[111]+{d}+!Shift");
+        assert_eq!(keybind.highlight(2), "Ctrl+'+'+[111]+{d}+!Shift\n         ^^^^^");
+    }
+
+    #[test]
+    fn parse_validated_str() {
+        let keybind : ParsedKeybind = "Ctrl+Alt+[111]+{d}".parse().unwrap();
+        assert_eq!(keybind.parameters_present().collect::<Vec<_>>(), ["d"]);
+
+        let err = "Ctrl+200".parse::<ParsedKeybind>().err().unwrap();
+        assert!(err.to_string().contains("`[200]`"));
+
+        let err = "Ctrl+{x}".parse::<ParsedKeybind>().err().unwrap();
+        assert!(err.to_string().starts_with("Unknown key parameter `{x}`.\nExpected one of: `{a}`, `{d}`, `{f}`, `{printable}`"));
+
+        assert!("Ctrl+".parse::<ParsedKeybind>().is_err());
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let err = "Ctrl+Ctrl+A".parse::<ParsedKeybind>().err().unwrap();
+        assert_eq!(err.to_string(), "`Ctrl` appears more than once in this keybind\n\nCtrl+Ctrl+A\n     ^^^^");
+
+        assert!("Ctrl+{d}+{d}".parse::<ParsedKeybind>().is_err());
+        assert!("Ctrl+[29]+[0x1d]".parse::<ParsedKeybind>().is_err());
+        assert!("Ctrl+{d}+1".parse::<ParsedKeybind>().is_ok());
+        assert!("Ctrl+A+!Shift".parse::<ParsedKeybind>().is_ok());
+
+        let err = "Ctrl+A+![29]+![29]".parse::<ParsedKeybind>().err().unwrap();
+        assert!(err.to_string().starts_with("`![29]` appears more than once"));
+
+        let err = "Ctrl+A+!Ctrl".parse::<ParsedKeybind>().err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "`Ctrl` is both pressed and not held (`!Ctrl`) in this keybind\n\nCtrl+A+!Ctrl\n       ^^^^^",
+        );
+    }
+
+    #[test]
+    fn resolved_key_checks() {
+        let keybind = "Ctrl+Esc+{d}+!Shift".parse::<ParsedKeybind>().unwrap();
+        let checks = keybind.resolved_key_checks(&syn::Ident::new("KEYS", proc_macro2::Span::call_site()));
+
+        // One per pair of keys, leaving out the key parameter.
+        assert_eq!(checks.to_string().matches("overlaps").count(), 3);
+        assert!(checks.to_string().contains("KEYS [0usize] . overlaps (& KEYS [1usize])"));
+    }
+
+    #[test]
+    fn parse_attr_options() {
+        let (keybind, options) = ParsedKeybindOptions::parse_attr("Ctrl+A, exclusive".parse().unwrap()).unwrap();
+        assert_eq!(keybind.iter().count(), 2);
+        assert!(options.exclusive);
+
+        let (_, options) = ParsedKeybindOptions::parse_attr("Ctrl+A".parse().unwrap()).unwrap();
+        assert!(!options.exclusive);
+
+        assert!(ParsedKeybindOptions::parse_attr("Ctrl+A, nope".parse().unwrap()).is_err());
+
+        let (_, options) = ParsedKeybindOptions::parse_attr("Logo+Space, state = MyWmState, exclusive".parse().unwrap()).unwrap();
+        assert_eq!(options.state_type().to_token_stream().to_string(), "MyWmState");
+
+        let sig : syn::Signature = syn::parse_str("fn AvSearch(wm : &mut MyWmState)").unwrap();
+        assert!(options.validate_state_arg(&sig).is_none());
+        let sig : syn::Signature = syn::parse_str("fn AvSearch(wm : &mut ())").unwrap();
+        assert!(options.validate_state_arg(&sig).is_some());
+    }
+
+    #[test]
+    fn parse_attr_hold_ms() {
+        let (_, options) = ParsedKeybindOptions::parse_attr("Power, hold_ms = 2000".parse().unwrap()).unwrap();
+        assert_eq!(options.hold_ms, Some(2000));
+
+        let (_, options) = ParsedKeybindOptions::parse_attr("Power".parse().unwrap()).unwrap();
+        assert_eq!(options.hold_ms, None);
+
+        assert!(ParsedKeybindOptions::parse_attr("Power, hold_ms = long".parse().unwrap()).is_err());
+        assert!(ParsedKeybindOptions::parse_attr("Power, hold_ms = -1".parse().unwrap()).is_err());
+    }
+
+    #[test]
+    fn default_combo_string() {
+        // What `default_combo_string()` returns: the keys only, as written.
+        let (keybind, _) = ParsedKeybindOptions::parse_attr("Logo + Space hold 500ms, exclusive".parse().unwrap()).unwrap();
+        assert_eq!(keybind.to_display_string(), "Logo+Space");
+    }
+
+    #[test]
+    fn parse_attr_sequence() {
+        let (keybind, options) = ParsedKeybindOptions::parse_attr("Ctrl+K then Ctrl+C, exclusive".parse().unwrap()).unwrap();
+        assert_eq!(keybind.to_display_string(), "Ctrl+K");
+        assert_eq!(options.then.iter().map(ParsedKeybind::to_display_string).collect::<Vec<_>>(), ["Ctrl+C"]);
+        assert!(options.exclusive);
+
+        let (keybind, options) = ParsedKeybindOptions::parse_attr("Logo then G then G".parse().unwrap()).unwrap();
+        assert_eq!(keybind.to_display_string(), "Logo");
+        assert_eq!(options.then.len(), 2);
+
+        assert!(ParsedKeybindOptions::parse_attr("Ctrl+K then".parse().unwrap()).is_err());
+        assert!(ParsedKeybindOptions::parse_attr("Ctrl+K then Ctrl+{d}".parse().unwrap()).is_err());
+    }
+
+    #[test]
+    fn parse_attr_custom_params() {
+        // Custom key parameters are resolved by name where the keybind is declared.
+        let (keybind, options) = ParsedKeybindOptions::parse_attr("Logo+{kp}, params = [kp]".parse().unwrap()).unwrap();
+        assert_eq!(options.params.len(), 1);
+        let last = keybind.iter().last().unwrap().to_lookup().to_string();
+        assert!(last.ends_with("AvKey :: Parameter (kp)"), "{last}");
+
+        let err = ParsedKeybindOptions::parse_attr("Logo+{kp}".parse().unwrap()).err().unwrap();
+        assert!(err.to_string().starts_with("Unknown key parameter `{kp}`"));
+
+        assert!(ParsedKeybindOptions::parse_attr("Logo+{kp}, params = kp".parse().unwrap()).is_err());
+    }
+
+    #[test]
+    fn parse_attr_anchor() {
+        let (_, options) = ParsedKeybindOptions::parse_attr("Alt+Tab, anchor = Alt".parse().unwrap()).unwrap();
+        assert_eq!(options.anchor.map(|k| k.to_display_string()).as_deref(), Some("Alt"));
+
+        let (_, options) = ParsedKeybindOptions::parse_attr("[56]+Tab, anchor = [56], exclusive".parse().unwrap()).unwrap();
+        assert_eq!(options.anchor.map(|k| k.to_display_string()).as_deref(), Some("[56]"));
+        assert!(options.exclusive);
+
+        let (_, options) = ParsedKeybindOptions::parse_attr("Alt+Tab".parse().unwrap()).unwrap();
+        assert!(options.anchor.is_none());
+
+        assert!(ParsedKeybindOptions::parse_attr("Alt+{d}, anchor = {d}".parse().unwrap()).is_err());
+        assert!(ParsedKeybindOptions::parse_attr("Alt+Tab, anchor = !Alt".parse().unwrap()).is_err());
+
+        // Other names for the keys are checked once resolved.
+        let keys = syn::Ident::new("KEYS", proc_macro2::Span::call_site());
+        for (attr, checked) in [
+            ("Alt+Tab, anchor = Alt", false),
+            ("Alt+Tab, anchor = [56]", true),
+            ("LeftAlt+Tab, anchor = [56]", true),
+            ("Escape+Tab, anchor = Esc", true),
+            ("Alt+Tab, anchor = Ctrl", true),
+        ] {
+            let (keybind, options) = ParsedKeybindOptions::parse_attr(attr.parse().unwrap()).unwrap();
+            let check = options.anchor_check(&keybind, &keys).to_string();
+            assert_eq!(check.contains("KEYS [0usize] . overlaps"), checked, "{attr}");
+        }
+    }
+
+    #[test]
+    fn parse_attr_hold_suffix() {
+        let (keybind, options) = ParsedKeybindOptions::parse_attr("Logo hold 500ms".parse().unwrap()).unwrap();
+        assert_eq!(keybind.to_display_string(), "Logo");
+        assert_eq!(options.hold_ms, Some(500));
+
+        let (keybind, options) = ParsedKeybindOptions::parse_attr("Ctrl+Power hold 2s, exclusive".parse().unwrap()).unwrap();
+        assert_eq!(keybind.to_display_string(), "Ctrl+Power");
+        assert_eq!(options.hold_ms, Some(2000));
+        assert!(options.exclusive);
+
+        for attr in ["Logo hold", "Logo hold 500", "Logo hold 1.5s", "Logo hold 5min", "Logo hold 5ms 5ms", "Logo hold 1s, hold_ms = 1000"] {
+            assert!(ParsedKeybindOptions::parse_attr(attr.parse().unwrap()).is_err(), "{attr}");
+        }
+    }
+
+    #[test]
+    fn misplaced_parameters() {
+        let misplaced = |s : &str| s.parse::<ParsedKeybind>().unwrap()
+            .misplaced_parameters()
+            .map(ParsedKey::to_display_string)
+            .collect::<Vec<_>>();
+
+        assert_eq!(misplaced("{d}+A"), ["{d}"]);
+        assert_eq!(misplaced("{d}+Ctrl+{f}+[30]+!Shift"), ["{d}", "{f}"]);
+        assert!(misplaced("Ctrl+{d}").is_empty());
+        assert!(misplaced("{d}+Ctrl+[42]").is_empty());
+        assert!(misplaced("A+{d}+!B").is_empty());
+
+        assert_eq!("{d}+Ctrl+A".parse::<ParsedKeybind>().unwrap().to_ordered_display_string(), "Ctrl+A+{d}");
+
+        let (_, options) = ParsedKeybindOptions::parse_attr("{d}+A, any_order".parse().unwrap()).unwrap();
+        assert!(options.any_order);
+    }
+
+    #[test]
+    fn parse_attr_trigger() {
+        let (_, options) = ParsedKeybindOptions::parse_attr("Logo+V".parse().unwrap()).unwrap();
+        assert_eq!(options.trigger, TriggerMode::Press);
+
+        let (_, options) = ParsedKeybindOptions::parse_attr("Logo+V, on = release".parse().unwrap()).unwrap();
+        assert_eq!(options.trigger, TriggerMode::Release);
+
+        let err = ParsedKeybindOptions::parse_attr("Logo+V, on = hold".parse().unwrap()).err().unwrap();
+        assert!(err.to_string().starts_with("Unknown trigger mode `hold`"));
+    }
+
+    #[test]
+    fn parse_attr_requires() {
+        let (_, options) = ParsedKeybindOptions::parse_attr(r#"PrintScreen, requires = ["screenshot", "clipboard"]"#.parse().unwrap()).unwrap();
+        let requires = options.requires.iter().map(|s| s.value()).collect::<Vec<_>>();
+        assert_eq!(requires, ["screenshot", "clipboard"]);
+
+        let (_, options) = ParsedKeybindOptions::parse_attr("PrintScreen, state = [u8; 4]".parse().unwrap()).unwrap();
+        assert!(options.requires.is_empty());
+        assert!(options.state.is_some());
+
+        assert!(ParsedKeybindOptions::parse_attr("PrintScreen, requires = screenshot".parse().unwrap()).is_err());
+    }
+
+    #[test]
+    fn hex_keycodes() {
+        let keybind : ParsedKeybind = syn::parse_str("Ctrl+[0x1d]").unwrap();
+        let code = keybind.iter().last().unwrap();
+
+        assert!(matches!(code, ParsedKey::Code(_, int) if int.base10_parse::<u32>().unwrap() == 29));
+        assert_eq!(code.to_lookup().to_string().replace(' ', ""), "::avkeys::AvKey::Key(29)");
+        assert!(code.is_modifier());
+
+        assert!(syn::parse_str::<ParsedKeybind>("Ctrl+[0x1_0000_0000]").is_err());
+    }
+
+    #[test]
+    fn parameter_lookup() {
+        let keybind : ParsedKeybind = syn::parse_str("Ctrl+{d}+A").unwrap();
+        let params = keybind.iter()
+            .filter_map(ParsedKey::to_parameter_lookup)
+            .map(|p| p.to_string().replace(' ', ""))
+            .collect::<Vec<_>>();
+
+        assert_eq!(params, ["::avkeys::AvKeyParameter::DigitKey"]);
+    }
+
+    #[test]
+    fn parse_not_held() {
+        let keybind : ParsedKeybind = syn::parse_str("Ctrl+A+!Shift").unwrap();
+        let last = keybind.iter().last().unwrap();
+
+        assert!(matches!(last, ParsedKey::NotHeld(_, _)));
+        assert!(matches!(last.inner(), ParsedKey::Name(ParsedKeyDisc::Ident(i)) if i == "Shift"));
+        assert!(syn::parse_str::<ParsedKeybind>("Ctrl+!{d}").is_err());
+    }
+}
@@ -1,8 +1,14 @@
 //!
 //! Macro to read [/usr/include/linux/input-event-codes.h]
 //! 
+//! Set `AVKEYS_INPUT_CODES_PATH` to read the header from elsewhere
+//! (e.g. when cross-compiling, or in Nix/Bazel sandboxes).
+//! 
+//! With the `vendored-codes` feature, a bundled copy of the header
+//! is used when the system one is absent (e.g. on macOS or Windows).
+//! 
 
-use std::{fs::File, io::Read};
+use std::fs;
 
 use proc_macro::{TokenStream, Span};
 use quote::quote;
@@ -11,19 +17,55 @@ use regex::Regex;
 
 
 
-/// Auto generate consts from [/usr/include/linux/input-event-codes.h]
+///
+/// Default location of the header.
+/// 
+const DEFAULT_PATH : &str = "/usr/include/linux/input-event-codes.h";
+
+///
+/// Bundled copy of the header, for builds without Linux headers.
+/// 
+#[cfg(feature = "vendored-codes")]
+const VENDORED : &str = include_str!("../input-event-codes.h");
+
+/// Auto generate consts from [/usr/include/linux/input-event-codes.h],
+/// or the header at `AVKEYS_INPUT_CODES_PATH` if set.
+/// 
+/// Takes the prefixes of the defines to capture, `KEY` if none are given:
+/// `keycodes!(KEY, BTN)` also captures mouse buttons (`BTN_LEFT`).
 #[proc_macro]
 pub fn keycodes(tkn : TokenStream) -> TokenStream {
-    let mut file = File::open("/usr/include/linux/input-event-codes.h")
-        .expect("/usr/include/linux/input-event-codes.h not present!");
+    let prefixes = match syn::parse::Parser::parse(
+        syn::punctuated::Punctuated::<syn::Ident, syn::Token![,]>::parse_terminated,
+        tkn,
+    ) {
+        Ok(p) if p.is_empty() => vec!["KEY".to_string()],
+        Ok(p) => p.iter().map(|i| i.to_string()).collect(),
+        Err(err) => return err.to_compile_error().into(),
+    };
 
-    let mut body = String::new();
+    let env_path = std::env::var("AVKEYS_INPUT_CODES_PATH").ok();
+    let path = env_path.clone()
+        .unwrap_or_else(|| DEFAULT_PATH.to_string());
 
-    file.read_to_string(&mut body)
-        .expect("Cannot read from /usr/include/linux/input-event-codes.h");
+    let body = match fs::read_to_string(&path) {
+        Ok(body) => body,
+        // An explicitly set path must exist.
+        #[cfg(feature = "vendored-codes")]
+        Err(_) if env_path.is_none() => VENDORED.to_string(),
+        Err(err) => {
+            let msg = format!(
+                "Cannot read the keycodes header at `{path}`: {err}.\n\
+                 Set `AVKEYS_INPUT_CODES_PATH` to the location of `input-event-codes.h`."
+            );
+            return quote! { compile_error!(#msg); }.into();
+        },
+    };
 
-    let line_expr = Regex::new(r#"#define (KEY_[0-9A-Za-z_]+)\s*((0x\d+)|(\d+))"#)
-        .unwrap();
+    let line_expr = Regex::new(&format!(
+        r#"#define ((?:{})_[0-9A-Za-z_]+)\s+((0x[0-9a-fA-F]+)|(\d+))\b"#,
+        prefixes.join("|"),
+    )).unwrap();
 
     let definitions = body
         .lines()
@@ -1,4 +1,4 @@
-use std::iter;
+use std::{collections::HashMap, iter};
 
 use syn::{punctuated::Punctuated, token::{Bracket, Brace}, LitInt, Ident, Token, parse::Parse, bracketed, LitStr, LitChar, braced, Item, Attribute};
 
@@ -11,6 +11,26 @@ pub enum KeyIdentifier {
     LitChar(LitChar)
 }
 
+impl KeyIdentifier {
+    pub fn span(&self) -> proc_macro2::Span {
+        match self {
+            KeyIdentifier::LitInt(i) => i.span(),
+            KeyIdentifier::Ident(ident) => ident.span(),
+            KeyIdentifier::LitChar(ch) => ch.span(),
+        }
+    }
+
+    ///
+    /// As written in the macro, e.g. `'-'` rather than `-`.
+    /// 
+    pub fn to_token_string(&self) -> String {
+        match self {
+            KeyIdentifier::LitChar(ch) => quote::quote!(#ch).to_string(),
+            _ => self.to_string(),
+        }
+    }
+}
+
 impl ToString for KeyIdentifier {
     fn to_string(&self) -> String {
         match self {
@@ -86,7 +106,26 @@ pub struct ParseKeyCodeDefinition {
     primary : KeyIdentifier,
     arrow   : Token![=>],
     value   : LitInt,
-    aliases : Option<ParseKeyCodeAliases>
+    aliases : Option<ParseKeyCodeAliases>,
+    params  : Vec<Attribute>,
+}
+
+///
+/// Membership of a key in a key parameter's array,
+/// `#[param(digit = 1)]`: the array's name, and the key's position in it.
+/// 
+pub struct KeyParameterTag {
+    pub name  : Ident,
+    pub index : LitInt,
+}
+
+impl Parse for KeyParameterTag {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        input.parse::<Token![=]>()?;
+
+        Ok(Self { name, index : input.parse()? })
+    }
 }
 
 impl ParseKeyCodeDefinition {
@@ -113,6 +152,19 @@ impl ParseKeyCodeDefinition {
         self.attributes.iter()
     }
 
+    ///
+    /// The key parameter arrays this key is tagged into, after
+    /// its definition: `Digit1 => 2 #[param(digit = 1)]`.
+    /// 
+    pub fn params(&self) -> syn::Result<Vec<KeyParameterTag>> {
+        self.params.iter()
+            .map(|attr| match attr.path.is_ident("param") {
+                true => attr.parse_args(),
+                false => Err(syn::Error::new_spanned(attr, "Expected `#[param(<name> = <index>)]` here")),
+            })
+            .collect()
+    }
+
     pub fn primary(&self) -> &KeyIdentifier {
         &self.primary
     }
@@ -128,7 +180,8 @@ impl Parse for ParseKeyCodeDefinition {
             aliases : match input.peek(Token![match]) {
                 true  => Some(input.parse()?),
                 false => None
-            }
+            },
+            params  : input.call(Attribute::parse_outer)?,
         })
     }
 }
@@ -156,4 +209,146 @@ impl KeyCodesCollection {
     pub fn iter(&self) -> impl Iterator<Item = &ParseKeyCodeDefinition> {
         self.contents.iter()
     }
-}
\ No newline at end of file
+
+    ///
+    /// The keycode arrays of the key parameters tagged with
+    /// `#[param(<name> = <index>)]`, in order of first appearance:
+    /// each array holds the code of the key tagged with each index.
+    /// 
+    /// Errors on a repeated or missing index.
+    /// 
+    pub fn parameter_arrays(&self) -> syn::Result<Vec<(Ident, Vec<u32>)>> {
+        let mut arrays : Vec<(Ident, Vec<Option<u32>>)> = vec![];
+
+        for key in self.iter() {
+            for tag in key.params()? {
+                let index : usize = tag.index.base10_parse()?;
+                let i = match arrays.iter().position(|(name, _)| *name == tag.name) {
+                    Some(i) => i,
+                    None => {
+                        arrays.push((tag.name.clone(), vec![]));
+                        arrays.len() - 1
+                    },
+                };
+
+                let codes = &mut arrays[i].1;
+                if codes.len() <= index {
+                    codes.resize(index + 1, None);
+                }
+
+                if codes[index].replace(key.code()).is_some() {
+                    return Err(syn::Error::new(
+                        tag.index.span(),
+                        format!("`{}` already has a key at index {index}", tag.name),
+                    ));
+                }
+            }
+        }
+
+        arrays.into_iter()
+            .map(|(name, codes)| {
+                let missing = codes.iter().position(Option::is_none);
+                match missing {
+                    Some(index) => Err(syn::Error::new(
+                        name.span(),
+                        format!("`{name}` has no key at index {index}"),
+                    )),
+                    None => Ok((name, codes.into_iter().flatten().collect())),
+                }
+            })
+            .collect()
+    }
+
+    ///
+    /// Ensures no two keys have the same keycode,
+    /// erroring on every repeat after the first.
+    /// 
+    pub fn validate_codes(&self) -> syn::Result<()> {
+        let mut seen = HashMap::new();
+        let errors = self.iter()
+            .filter_map(|k| match seen.get(&k.code()) {
+                Some(first) => Some(syn::Error::new(
+                    k.value.span(),
+                    format!("`{}` has the same keycode ({}) as `{first}`", k.primary().to_string(), k.code()),
+                )),
+                None => {
+                    seen.insert(k.code(), k.primary().to_string());
+                    None
+                },
+            })
+            .collect::<Vec<_>>();
+
+        combine_errors(errors)
+    }
+
+    ///
+    /// Ensures no alias is another key's primary name, which would
+    /// make looking that name up ambiguous, erroring on every such alias.
+    /// 
+    pub fn validate_primary_collisions(&self) -> syn::Result<()> {
+        let primaries = self.iter()
+            .map(|k| k.primary().to_string())
+            .collect::<Vec<_>>();
+
+        let errors = self.iter()
+            .flat_map(|k| k.aliases().map(move |a| (k.primary(), a)))
+            .filter_map(|(primary, alias)| match alias {
+                KeyIdentifier::Ident(ident) if *ident != primary.to_string() => Some((primary, ident)),
+                _ => None,
+            })
+            .filter(|(_, ident)| primaries.contains(&ident.to_string()))
+            .map(|(primary, ident)| syn::Error::new(
+                ident.span(),
+                format!("`{ident}` is the primary name of another key, so it cannot be an alias of `{}`", primary.to_string()),
+            ))
+            .collect::<Vec<_>>();
+
+        combine_errors(errors)
+    }
+
+    ///
+    /// Ensures no name, char or number is an alias of more than one key,
+    /// erroring on every repeat after the first (pointing at both).
+    /// 
+    pub fn validate_aliases(&self) -> syn::Result<()> {
+        let mut seen = HashMap::new();
+        let errors = self.iter()
+            .enumerate()
+            .flat_map(|(i, k)| k.aliases().map(move |a| (i, k.primary(), a)))
+            .filter_map(|(i, primary, alias)| {
+                let key = (std::mem::discriminant(alias), alias.to_string());
+                match seen.get(&key) {
+                    Some((first_i, _, _)) if *first_i == i => None,
+                    Some((_, first, first_span)) => {
+                        let mut e = syn::Error::new(
+                            alias.span(),
+                            format!("`{}` is already an alias of `{first}`", alias.to_token_string()),
+                        );
+                        e.combine(syn::Error::new(*first_span, format!("`{}` is first aliased here", alias.to_token_string())));
+                        Some(e)
+                    },
+                    None => {
+                        seen.insert(key, (i, primary.to_string(), alias.span()));
+                        None
+                    },
+                }
+            })
+            .collect::<Vec<_>>();
+
+        combine_errors(errors)
+    }
+}
+
+///
+/// Combines errors into one reporting all of them, if there are any.
+/// 
+fn combine_errors(errors : Vec<syn::Error>) -> syn::Result<()> {
+    let mut errors = errors.into_iter();
+    match errors.next() {
+        Some(mut e) => {
+            errors.for_each(|err| e.combine(err));
+            Err(e)
+        },
+        None => Ok(()),
+    }
+}
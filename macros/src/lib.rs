@@ -10,7 +10,7 @@
 mod keycode;
 
 use convert_case::Casing;
-use ::avkeys_common::{ParsedKey, ParsedKeybind};
+use ::avkeys_common::{ParsedKey, ParsedKeybindOptions, TriggerMode};
 use keycode::{KeyIdentifier, KeyCodesCollection};
 use proc_macro::{Diagnostic, Level, TokenStream};
 use proc_macro2::Span;
@@ -52,6 +52,14 @@ use syn::{spanned::Spanned, ItemFn};
 /// Some of these aliases may be punctuation,
 /// so to use them from a macro context, escape them by
 /// putting them in character literals : `':'`, `'\\'`, `','`, `'#'`
+///
+/// ##### Either Side Modifiers
+///
+/// The side-less modifier names `Ctrl`, `Shift`, `Alt`, and `Meta` (or `Logo`, `Win`)
+/// match that modifier on either side of the keyboard (`AvKey::EitherSide`):
+/// `Ctrl+C` fires for `LeftCtrl+C` and `RightCtrl+C`. Name a side (`LeftCtrl`) to
+/// only match that key. Negated modifiers are never held on either side
+/// (`!Shift` rules out `LeftShift` and `RightShift`).
 /// 
 /// | **Example** | `Ctrl+Alt+Del` |
 /// |-------------|----------------|
@@ -60,13 +68,15 @@ use syn::{spanned::Spanned, ItemFn};
 /// #### Linux Key Codes: `[16]`, `[63]`
 /// These can be found in the Linux headers [/usr/include/linux/input-event-codes.h].
 ///
-/// These must be surrounded by \[square\] brackets.
+/// These must be surrounded by \[square\] brackets &mdash; a bare number
+/// (`1`) always names a digit key, so `Ctrl+200` is an error, not keycode `200`.
+/// Codes may be written in hex, as they are in the header: `[0x6f]` is `[111]`.
 ///
 /// | **Example** | `Ctrl+Alt+[111]` |
 /// |-------------|---------------------|
 /// |             |                     |
 ///
-/// #### Key Paramaters: `{d}`, `{f}`
+/// #### Key Paramaters: `{d}`, `{f}`, `{a}`
 /// Key parameters allow for numerous similar key combinations to have a shared action.
 ///
 /// For example, `Ctrl+1` to `Ctrl+9` could switch the active tab to `1` to `9`, depending
@@ -75,8 +85,14 @@ use syn::{spanned::Spanned, ItemFn};
 ///
 /// Look at `AvKeyParameter` for more information on key parameters.  
 ///
-/// In the callback function, you can optionally add this key parameter into the callback
-/// function, using the example syntax below.
+/// Other key parameters can be declared with `keyparameters!`, and used the same way
+/// (e.g. `{kp}`) once listed in the `params = [kp]` option: they are looked up by name
+/// where the keybind is declared. Any other short code is an error.
+///
+/// In the callback function, declare one argument (after `state`) for each key parameter:
+/// arguments are bound to key parameters by position, in the order they appear in the
+/// combination. The argument's type can be any `FromKeyParameter` type &mdash;
+/// usually `usize`, or `char` for `{printable}`.
 ///
 /// **Example**
 ///
@@ -85,13 +101,97 @@ use syn::{spanned::Spanned, ItemFn};
 /// /// Pet the cute kitty-cat `times` amount of times.
 /// ///
 /// #[AvKeybind(Ctrl+Shift+{d})]
-/// pub fn PetKitty(state : &mut (...), times : d) {
-///     for i in 1..(times.value()) {
-///         println!("Petted the kitty: {times}");
+/// pub fn PetKitty(state : &mut (...), times : usize) {
+///     for i in 1..=times {
+///         println!("Petted the kitty: {i}");
 ///     }
 /// }
 /// ```
 ///
+/// #### Sequences: `Ctrl+K then Ctrl+C`
+/// Combinations separated by `then` must be pressed one after another (`AvKeybind::then`),
+/// e.g. Vim-style `Logo then G then G`. `+` joins the keys of each combination,
+/// while `,` is kept for the options, so it can't separate steps; `then` is never a key name.
+/// Key parameters may only appear in the first combination.
+///
+/// | **Example** | `Ctrl+K then Ctrl+C` |
+/// |-------------|----------------------|
+/// |             |                      |
+///
+/// #### Negated Keys: `!Shift`
+/// A key name or code prefixed with `!` must *not* be held for the keybind to fire.
+///
+/// | **Example** | `Ctrl+A+!Shift` |
+/// |-------------|-----------------|
+/// |             |                 |
+///
+/// ### Options
+/// After the keys, a comma-separated list of options:
+/// * `exclusive` &mdash; only fire when exactly the keybind's modifiers are held,
+///   e.g. `#[AvKeybind(Ctrl+A, exclusive)]` never fires for `Ctrl+Shift+A`.
+/// * `on = press | release` &mdash; whether the keybind fires as its keys are
+///   pressed (the default), or as they are released, e.g. for push-to-talk.
+/// * `requires = ["<capability>", ...]` &mdash; capabilities of the compositor the
+///   keybind needs (e.g. `"screenshot"`); a `Dispatcher` skips it when one is missing.
+/// * `hold_ms = <milliseconds>` &mdash; how long the keys must be held for the keybind
+///   to fire, e.g. `#[AvKeybind(Power, hold_ms = 2000)]` (`AvKeybind::hold_duration`).
+///   This can also be written as a `hold` suffix to the keys, in `ms` or `s`:
+///   `#[AvKeybind(Power hold 2s)]`.
+/// * `anchor = <Key>` &mdash; the modifier whose release ends a repeated-chord interaction,
+///   e.g. `#[AvKeybind(Alt+Tab, anchor = Alt)]` (`AvKeybind::anchor_modifier`). It must be
+///   one of the keybind's keys, by any name or code (`anchor = [56]` for `Alt`).
+///   It must be written as one of the keybind's keys.
+/// * `params = [<code>, ...]` &mdash; custom key parameters (declared with `keyparameters!`)
+///   used in the keys, e.g. `#[AvKeybind(Logo+{kp}, params = [kp])]`.
+/// * `any_order` &mdash; silences the warning for key parameters written before
+///   fixed non-modifier keys (`{d}+A` rather than `A+{d}`).
+/// * `state = <Type>` &mdash; the type of state passed to the callback, `()` by default.
+///   The callback's first argument must then be `&mut <Type>`:
+///
+/// ```ignore
+/// #[AvKeybind(Logo+Space, state = MyWmState)]
+/// pub fn AvSearch(wm : &mut MyWmState) {
+///     wm.open_search();
+/// }
+/// ```
+///
+/// ### Return Values
+/// The callback may return a value, e.g. a command for the compositor to carry out;
+/// the keybind then implements `AvKeybind<State, ReturnType>`, and `run` passes it on:
+///
+/// ```ignore
+/// #[AvKeybind(Logo+Q)]
+/// pub fn CloseWindow(state : &mut ()) -> WmCommand {
+///     WmCommand::Close
+/// }
+/// ```
+///
+/// ### Registration
+/// The generated struct implements `Default` (with no override), and has a
+/// `DEFAULT_KEYS` constant, with `default_combo_string()` giving the same keys as
+/// written in the attribute (`"Logo+Space"`), which `parse_keybind` reads back.
+/// `keybinds! { AvSearch, PowerOptions, ... }` builds a `Registry` of the listed keybinds, failing to compile
+/// when two of them have the same default keys.
+///
+/// With the `inventory` feature, every keybind (with the default `()` state
+/// and returning `()`)
+/// is also submitted to a global collection, so `Registry::from_inventory()`
+/// can gather them all without registering each one by hand.
+///
+/// ### Overrides
+/// The generated struct holds the user's override of the keys, if any
+/// (`AvKeybind::keys_override`). `is_modified()` tells whether it differs
+/// from the defaults, and `reset()` drops it.
+///
+/// Overrides are saved and loaded by the keybind's ID, `id()`: the callback's
+/// name as written (`AvSearch`), so a config maps `AvSearch = "Logo+Space"`.
+/// IDs are not namespaced by crate or module, so two keybinds named alike
+/// clash once registered together &mdash; only the first is ever found by ID.
+///
+/// An override must keep the key parameters the callback takes: they are
+/// listed in the struct's `EXPECTED_PARAMS` constant, and `accepts_override(&keys)`
+/// checks a new combination has exactly those, in order. `set_keys(keys)` (or
+/// the `with_keys(keys)` constructor) only overrides the keys if they do.
 ///
 /// ### Full Example
 /// ```ignore
@@ -110,21 +210,32 @@ use syn::{spanned::Spanned, ItemFn};
 #[proc_macro_attribute]
 #[allow(non_snake_case)]
 pub fn AvKeybind(attrs: TokenStream, body: TokenStream) -> TokenStream {
-    // 1. Parse Default Keybind
+    // 1. Parse and Validate Default Keybind
 
-    let keybind: ParsedKeybind = match syn::parse(attrs).map_err(|err| {
-        syn::Error::new(
-            err.span(),
-            "Expected a + seperated non-trailing list of keys here:\n\
-                Full Example: #[AvKeybind(Ctrl+[111]+{d})]",
-        )
-    }) {
+    let (keybind, options) = match ParsedKeybindOptions::parse_attr(attrs.into()) {
         Ok(v) => v,
         Err(err) => {
             return err.into_compile_error().into();
         }
     };
 
+    // 1a. Lint the key order (key parameters go last).
+    if !options.any_order {
+        for key in keybind.misplaced_parameters() {
+            Diagnostic::spanned(
+                key.span().unwrap(),
+                Level::Warning,
+                format!(
+                    "Key parameter `{}` is written before a fixed key; consider `{}`.\n\
+                        To keep this order, add the `any_order` option.",
+                    key.to_display_string(),
+                    keybind.to_ordered_display_string(),
+                ),
+            )
+            .emit();
+        }
+    }
+
     // 2. Parse Implementation function.
 
     let func: ItemFn = match syn::parse(body).map_err(|err| {
@@ -156,14 +267,14 @@ pub fn AvKeybind(attrs: TokenStream, body: TokenStream) -> TokenStream {
 
     // 2b. Validate callback signature.
 
-    // 2b (i) Validate Key Parameter Names
-    match keybind.validate_parameter_names() {
+    // Key Parameters in the Function Delcaration
+
+    // 2b (i) Validate the state argument's type
+    match options.validate_state_arg(&func.sig) {
         Some(err) => return err.into(),
         None => {}
     };
 
-    // Key Parameters in the Function Delcaration
-
     // 2b (ii) Validate Key Parameters are in function signature
     //         Ensure all key parameters are present in the function signature.
     match keybind.validate_func_sign_against_key_params(&func.sig) {
@@ -194,6 +305,12 @@ pub fn AvKeybind(attrs: TokenStream, body: TokenStream) -> TokenStream {
 
     let default_keys_count = default_keys.len();
 
+    let default_combo = keybind.to_display_string();
+
+    let expected_params = keybind.iter()
+        .filter_map(ParsedKey::to_parameter_lookup)
+        .collect::<Vec<_>>();
+
     let body = func.block;
 
     // FIXME(Sammy99jsp):   Auto-suggestions do not always behave
@@ -209,38 +326,209 @@ pub fn AvKeybind(attrs: TokenStream, body: TokenStream) -> TokenStream {
 
     let vis = func.vis;
 
+    // 3c. Identifier and description (from the rustdoc comments).
+    let keybind_id = keybind_name.to_string();
+    let description = func.attrs.iter()
+        .filter_map(|a| match a.parse_meta() {
+            Ok(syn::Meta::NameValue(syn::MetaNameValue { path, lit: syn::Lit::Str(s), .. }))
+                if path.is_ident("doc") => Some(s.value()),
+            _ => None,
+        })
+        .map(|ln| ln.trim().to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string();
+
+    let exclusive = options.exclusive;
+    let trigger = match options.trigger {
+        TriggerMode::Press => quote! { ::avkeys_common::TriggerMode::Press },
+        TriggerMode::Release => quote! { ::avkeys_common::TriggerMode::Release },
+    };
+    let state_type = options.state_type();
+    let return_type = match &func.sig.output {
+        syn::ReturnType::Default => quote! { () },
+        syn::ReturnType::Type(_, ty) => quote! { #ty },
+    };
+    let returns_unit = match &func.sig.output {
+        syn::ReturnType::Default => true,
+        syn::ReturnType::Type(_, ty) => matches!(&**ty, syn::Type::Tuple(t) if t.elems.is_empty()),
+    };
+    let requires = &options.requires;
+    let then = options.then
+        .iter()
+        .map(|step| step.iter().map(ParsedKey::to_lookup).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    // Only sequences override `then()`.
+    let then = (!then.is_empty())
+        .then(|| quote! {
+            fn then(&self) -> &[::avkeys::KeyCombo] {
+                static THEN : ::std::sync::OnceLock<Vec<::avkeys::KeyCombo>> = ::std::sync::OnceLock::new();
+                THEN.get_or_init(|| vec![
+                    #( ::avkeys::KeyCombo::new(vec![#(#then),*]) ),*
+                ])
+            }
+        })
+        .unwrap_or_default();
+
+    // `None` unless an anchor was given.
+    let anchor = options.anchor
+        .as_ref()
+        .map(|key| {
+            let key = key.to_lookup();
+            quote! {
+                fn anchor_modifier(&self) -> Option<::avkeys_common::KeyCode> {
+                    ::avkeys::AvKey::key(&#key)
+                }
+            }
+        })
+        .unwrap_or_default();
+
+    let hold_ms = match options.hold_ms {
+        Some(ms) => quote! { Some(#ms) },
+        None => quote! { None },
+    };
+
+    // The callback's own name for the state argument, if it takes one.
+    let state_pat = match func.sig.inputs.first() {
+        Some(syn::FnArg::Typed(arg)) => {
+            let pat = &arg.pat;
+            quote! { #pat }
+        },
+        _ => quote! { _ },
+    };
+
     let keybind_default_const = keybind_name.to_string()
         .to_case(convert_case::Case::ScreamingSnake) + "_CONST";
 
     let keybind_default_const = syn::Ident::new(&keybind_default_const, Span::call_site());
 
+    // Keys which are the same once resolved (`Esc+Escape`) fail to compile.
+    let resolved_key_checks = keybind.resolved_key_checks(&keybind_default_const);
+    let anchor_check = options.anchor_check(&keybind, &keybind_default_const);
+
+    // 3d. Submit to the global collection (see `Registry::from_inventory`).
+    let registration = (cfg!(feature = "inventory") && options.state.is_none() && returns_unit)
+        .then(|| quote! {
+            ::avkeys::inventory::submit! {
+                ::avkeys::KeybindRegistration::new(|| ::std::boxed::Box::new(#keybind_name(None)))
+            }
+        })
+        .unwrap_or_default();
+
     quote! {
         #(#attrs)*
-        #vis struct #keybind_name(Option<Vec< ::avkeys_common::AvKey >>);
+        #vis struct #keybind_name(Option<Vec< ::avkeys::AvKey >>);
 
-        const #keybind_default_const : [::avkeys_common::AvKey ; #default_keys_count]= [
+        const #keybind_default_const : [::avkeys::AvKey ; #default_keys_count]= [
             #(#default_keys),*
         ];
 
-        impl AvKeybind for #keybind_name {
-            fn default_keys() -> &'static [::avkeys_common::AvKey]
+        #resolved_key_checks
+        #anchor_check
+
+        impl #keybind_name {
+            ///
+            /// The combination this keybind ships with, for use in `const` contexts.
+            ///
+            pub const DEFAULT_KEYS : &'static [::avkeys::AvKey] = &#keybind_default_const;
+
+            ///
+            /// The combination this keybind ships with, as written in its
+            /// attribute (e.g. for a default config file).
+            ///
+            pub fn default_combo_string() -> String {
+                String::from(#default_combo)
+            }
+
+            ///
+            /// The key parameters the callback takes, in order.
+            ///
+            pub const EXPECTED_PARAMS : &'static [::avkeys::AvKeyParameter] = &[#(#expected_params),*];
+
+            ///
+            /// Whether `keys` can replace this keybind's keys: they must have
+            /// exactly its [EXPECTED_PARAMS](Self::EXPECTED_PARAMS).
+            ///
+            pub fn accepts_override(keys : &[::avkeys::AvKey]) -> bool {
+                ::avkeys::accepts_parameters(Self::EXPECTED_PARAMS, keys)
+            }
+
+            ///
+            /// This keybind with its keys overridden (see `AvKeybind::set_keys`).
+            ///
+            pub fn with_keys(keys : Vec<::avkeys::AvKey>) -> Result<Self, ::avkeys::OverrideError> {
+                let mut keybind = Self(None);
+                <Self as ::avkeys::AvKeybind<#state_type, #return_type>>::set_keys(&mut keybind, keys)?;
+                Ok(keybind)
+            }
+        }
+
+        impl ::std::default::Default for #keybind_name {
+            fn default() -> Self {
+                Self(None)
+            }
+        }
+
+        impl ::avkeys::AvKeybind<#state_type, #return_type> for #keybind_name {
+            fn default_keys() -> &'static [::avkeys::AvKey]
                 where Self : Sized
             {
                 &#keybind_default_const
             }
 
-            fn keys(&self) -> &[::avkeys_common::AvKey] {
+            fn keys(&self) -> &[::avkeys::AvKey] {
                 self.0.as_ref()
                     .map(|v| v.as_slice())
                     .unwrap_or(Self::default_keys())
             }
 
-            fn run(&self, state : &mut (), __params__ : Vec<usize>) {
+            fn keys_override(&mut self) -> &mut Option<Vec<::avkeys::AvKey>> {
+                &mut self.0
+            }
+
+            fn is_modified(&self) -> bool {
+                self.0.as_deref()
+                    .map_or(false, |keys| keys != Self::default_keys())
+            }
+
+            fn run(&self, #state_pat : &mut #state_type, __params__ : Vec<usize>) -> #return_type {
                 #pre_assignments
                 ::std::mem::drop(__params__);
                 #(#body)*
             }
+
+            fn id(&self) -> &'static str {
+                #keybind_id
+            }
+
+            fn description(&self) -> &'static str {
+                #description
+            }
+
+            fn exclusive_modifiers(&self) -> bool {
+                #exclusive
+            }
+
+            fn trigger(&self) -> ::avkeys_common::TriggerMode {
+                #trigger
+            }
+
+            fn requires(&self) -> &'static [&'static str] {
+                &[#(#requires),*]
+            }
+
+            fn hold_duration_ms(&self) -> Option<u64> {
+                #hold_ms
+            }
+
+            #then
+
+            #anchor
         }
+
+        #registration
     }
     .into()
 }
@@ -249,14 +537,15 @@ pub fn AvKeybind(attrs: TokenStream, body: TokenStream) -> TokenStream {
 /// ## keycodes!
 /// 
 /// Generates an enum of keycodes,
-/// and some matching/parsing functions.
+/// and some matching/parsing functions, and `Key::ALL` (or `Key::iter()`)
+/// listing every key (`Key::VARIANT_NAMES` by name).
 /// 
 /// ### Syntax &mdash; Keycode Definition
 /// The `keycodes!` macro takes a collection of
 /// keycode definitions, which are in the folowing format:
 /// 
 /// #### Format
-/// `<PrimaryName> => <code> [ match [<KeyDiscrim>, ...] ]`
+/// `<PrimaryName> => <code> [ match [<KeyDiscrim>, ...] ] [ #[param(<name> = <index>)] ]`
 /// 
 /// | Token         | Description                      | Example |
 /// | :---          | :-----------------------------   | :---    |
@@ -265,6 +554,12 @@ pub fn AvKeybind(attrs: TokenStream, body: TokenStream) -> TokenStream {
 /// | `match` *(Optional)* | Use this `match` keyword in conjunction with the alias array to define aliases for this key. |  |
 /// | `KeyDiscrim` *(Optional)* | Any of: a char literal; an integer literal; an identifier. Adding a char or int literal will add a case to the `TryFrom` of this enum | `';'`, `','`, `12`, `0x56`, `Ident` |
 /// 
+/// #### Serde
+/// When the invoking crate's `serde` feature is enabled, the generated enum
+/// derives `Serialize` and `Deserialize`. Every variant serializes as its key's
+/// *primary* name (`Ctrl` serializes as `"LeftCtrl"`), while any alias
+/// deserializes to its own variant.
+///
 /// #### Rustdoc
 /// keycodes! speaks Rustdoc!
 /// 
@@ -278,9 +573,14 @@ pub fn AvKeybind(attrs: TokenStream, body: TokenStream) -> TokenStream {
 /// In the future, this macro may also export these alias definitions
 /// to an external file for auto-gen'd end user documentation.
 /// 
+/// #### Key Parameter Arrays
+/// After a definition, `#[param(<name> = <index>)]` puts the key at `index` in
+/// the keycode array `Key::<NAME>_KEYS` (e.g. `Key::DIGIT_KEYS`), from which key
+/// parameters take their keys. Every index up to the highest must have a key.
+/// 
 /// #### Example Definitions
 /// * `Escape => 1 match [Esc, ]`
-/// * `Digit1 => 2 match [1, '1', ]`
+/// * `Digit1 => 2 match [1, '1', ] #[param(digit = 1)]`
 /// * `Digit0 => 10`
 /// 
 /// ### Example
@@ -316,6 +616,42 @@ pub fn keycodes(body : TokenStream) -> TokenStream {
         }.into()
     };
 
+    if aliases.iter().next().is_none() {
+        return quote!{
+            compile_error!("Expected at least one keycode definition:\n{\n    Key1 => 2,\n    // ...\n}");
+        }.into();
+    }
+
+    if let Err(err) = aliases.validate_codes() {
+        return err.into_compile_error().into();
+    }
+
+    if let Err(err) = aliases.validate_primary_collisions() {
+        return err.into_compile_error().into();
+    }
+
+    if let Err(err) = aliases.validate_aliases() {
+        return err.into_compile_error().into();
+    }
+
+    let parameter_arrays = match aliases.parameter_arrays() {
+        Ok(arrays) => arrays,
+        Err(err) => return err.into_compile_error().into(),
+    };
+
+    let parameter_arrays = parameter_arrays
+        .iter()
+        .map(|(name, codes)| {
+            let ident = syn::Ident::new(&format!("{}_KEYS", name.to_string().to_uppercase()), name.span());
+            let count = codes.len();
+            let doc = format!("Keycodes of the keys tagged `#[param({name} = <index>)]`, by index.");
+
+            quote! {
+                #[doc = #doc]
+                pub const #ident : [::avkeys_common::KeyCode; #count] = [#(#codes),*];
+            }
+        });
+
     let definitions = aliases
         .iter()
         .flat_map(|k| {
@@ -331,6 +667,16 @@ pub fn keycodes(body : TokenStream) -> TokenStream {
                             .then(|| quote! { #[doc = "***"] })
                             .unwrap_or_default();
 
+                        // Alias variants serialize as their primary name,
+                        // so serialized forms stay stable.
+                        let pri_name = pri.to_string();
+                        let alias_name = alias_ident.to_string();
+                        let serde_attr = if alias_name == pri_name {
+                            quote! { #[cfg_attr(feature = "serde", serde(rename = #pri_name))] }
+                        } else {
+                            quote! { #[cfg_attr(feature = "serde", serde(rename(serialize = #pri_name, deserialize = #alias_name)))] }
+                        };
+
                         if alias_ident.to_string() == pri.to_string() {
                             let doc_comment = if k.aliases().count() > 1 {
                                 format!("Aliases ({}): {}", k.aliases().count() -1, k.aliases().filter(|al| al.to_string() != pri.to_string()).map(|al| format!("`{}`", al.to_string()))
@@ -343,6 +689,7 @@ pub fn keycodes(body : TokenStream) -> TokenStream {
                                 #(#attrs)*
                                 #spacing
                                 #[doc = #doc_comment]
+                                #serde_attr
                                 #alias_ident,
                             }
                         } 
@@ -353,6 +700,7 @@ pub fn keycodes(body : TokenStream) -> TokenStream {
                             #(#attrs)*
                             #spacing
                             #[doc = #doc_comment]
+                            #serde_attr
                             #alias_ident,
                         }
                         
@@ -367,7 +715,9 @@ pub fn keycodes(body : TokenStream) -> TokenStream {
             })
         });
     
-    let lookup_str = aliases
+    // Every name (and char/int alias), sorted by bytes for
+    // `Key::lookup`'s binary search; repeats keep the first key.
+    let mut name_table = aliases
         .iter()
         .flat_map(|k| {
             let p = match k.primary() {
@@ -381,7 +731,36 @@ pub fn keycodes(body : TokenStream) -> TokenStream {
                     panic!();
                 },
             };
-            
+
+            k.aliases()
+                .map(move |a| {
+                    let (s, span) = match a {
+                        KeyIdentifier::LitInt(i) => (i.to_string(), i.span()),
+                        KeyIdentifier::Ident(ident) => (ident.to_string(), ident.span()),
+                        KeyIdentifier::LitChar(s) => (s.value().to_string(), s.span()),
+                    };
+
+                    (s, span, p)
+                })
+        })
+        .collect::<Vec<_>>();
+
+    name_table.sort_by(|(l, ..), (r, ..)| l.as_bytes().cmp(r.as_bytes()));
+    name_table.dedup_by(|(l, ..), (r, ..)| l == r);
+
+    let name_table = name_table
+        .iter()
+        .map(|(s, span, p)| {
+            let raw_byte_str = syn::LitByteStr::new(s.as_bytes(), *span);
+            quote! { (#raw_byte_str, Self::#p) }
+        });
+
+    // Same names as `NAME_TABLE`, straight to the keycode.
+    let code_of_str = aliases
+        .iter()
+        .flat_map(|k| {
+            let code = k.code();
+
             k.aliases()
                 .map(move |a| {
                     let (s, span) = match a {
@@ -393,11 +772,11 @@ pub fn keycodes(body : TokenStream) -> TokenStream {
                     let raw_byte_str = syn::LitByteStr::new(s.as_bytes(), span);
 
                     quote! {
-                        #raw_byte_str => Some(Self::#p),
+                        #raw_byte_str => Some(#code),
                     }
                 })
         });
-    
+
     let lookup_ints = aliases
         .iter()
         .map(|k| {
@@ -446,12 +825,22 @@ pub fn keycodes(body : TokenStream) -> TokenStream {
         .iter()
         .flat_map(|k| {
             let p = k.code();
-            let names = k.aliases()
-                .filter_map(|alias| match alias {
-                    KeyIdentifier::LitInt(_) => None,
-                    KeyIdentifier::Ident(ident) => Some(ident.to_string()),
-                    KeyIdentifier::LitChar(c) => Some(c.value().to_string()),
-                }).collect::<Vec<_>>(); 
+
+            // The primary name first, so every variant has at least one name.
+            let mut names = match k.primary() {
+                KeyIdentifier::Ident(ident) => vec![ident.to_string()],
+                _ => unreachable!(),
+            };
+
+            for name in k.aliases().filter_map(|alias| match alias {
+                KeyIdentifier::LitInt(_) => None,
+                KeyIdentifier::Ident(ident) => Some(ident.to_string()),
+                KeyIdentifier::LitChar(c) => Some(c.value().to_string()),
+            }) {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
 
             k.aliases()
                 .filter_map(move |a| match a {
@@ -479,15 +868,33 @@ pub fn keycodes(body : TokenStream) -> TokenStream {
             }
         });
 
+    let primaries = aliases
+        .iter()
+        .map(|k| match k.primary() {
+            KeyIdentifier::Ident(ident) => ident,
+            _ => unreachable!(),
+        });
+
+    let primary_names = aliases
+        .iter()
+        .map(|k| match k.primary() {
+            KeyIdentifier::Ident(ident) => ident.to_string(),
+            _ => unreachable!(),
+        })
+        .collect::<Vec<_>>();
+
+    let key_count = primary_names.len();
+
     let ident_names_str = idents
         .iter()
         .map(|(_, ident, names)| quote! {
-            Self::#ident => vec![#(#names),*] 
+            Self::#ident => &[#(#names),*]
         });
 
     quote! {
         #(#attrs)*
         #[derive(Debug, Clone, Copy)]
+        #[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
         pub enum Key {
             #(#definitions)*
         }
@@ -496,15 +903,99 @@ pub fn keycodes(body : TokenStream) -> TokenStream {
 
 
         impl Key {
+            ///
+            /// Every key, by its primary name, in declaration order.
+            ///
+            pub const ALL : &'static [Key] = &[#(Self::#primaries),*];
 
-            pub const fn lookup<'a, I : ~const IntoAvKeyDiscrim<'a>>(a : I) -> Option<Self> {
-                let a : avkeys_common::AvKeyDiscrim = IntoAvKeyDiscrim::into_discrim(a);
-            
+            ///
+            /// Iterates over every key (see [Key::ALL]), e.g. for a key picker.
+            ///
+            pub fn iter() -> impl Iterator<Item = Key> {
+                Self::ALL.iter().copied()
+            }
+
+            ///
+            /// The primary name of every key, in the same order as [Key::ALL]
+            /// (e.g. for name pickers; see [Key::lookup] to go back).
+            ///
+            pub const VARIANT_NAMES : &'static [&'static str] = &[#(#primary_names),*];
+
+            ///
+            /// Number of keys, i.e. the length of [Key::ALL].
+            ///
+            pub const KEY_COUNT : usize = #key_count;
+
+            #(#parameter_arrays)*
+
+            ///
+            /// Finds a key by name or alias (`"Ctrl"`), character (`'+'`), or keycode (`29`).
+            ///
+            /// Keycodes always give the key's primary variant.
+            ///
+            pub fn lookup<'a>(d : impl Into<::avkeys_common::AvKeyDiscrim<'a>>) -> Option<Self> {
+                Self::lookup_discrim(d.into())
+            }
+
+            ///
+            /// Like [Key::lookup], for use in `const` contexts.
+            ///
+            pub const fn lookup_const<'a, I : ~const IntoAvKeyDiscrim<'a>>(a : I) -> Option<Self> {
+                Self::lookup_discrim(IntoAvKeyDiscrim::into_discrim(a))
+            }
+
+            ///
+            /// Keycode of a key by name or alias (`"Esc"`), for use in `const` contexts.
+            ///
+            pub const fn code_of(name : &str) -> Option<::avkeys_common::KeyCode> {
+                match name.as_bytes() {
+                    #(#code_of_str)*
+                    _ => None
+                }
+            }
+
+            ///
+            /// Every name and alias (`b"Ctrl"`, `b"+"`), sorted by bytes,
+            /// with the key (its primary variant) it names.
+            ///
+            const NAME_TABLE : &'static [(&'static [u8], Key)] = &[#(#name_table),*];
+
+            ///
+            /// Binary search of [Key::NAME_TABLE].
+            ///
+            const fn find_name(name : &[u8]) -> Option<Self> {
+                let (mut lo, mut hi) = (0, Self::NAME_TABLE.len());
+
+                while lo < hi {
+                    let mid = (lo + hi) / 2;
+                    let (entry, key) = Self::NAME_TABLE[mid];
+
+                    // Byte-wise comparison, as `Ord` isn't `const`.
+                    let mut i = 0;
+                    while i < entry.len() && i < name.len() && entry[i] == name[i] {
+                        i += 1;
+                    }
+
+                    let less = match (i < entry.len(), i < name.len()) {
+                        (true, true) => entry[i] < name[i],
+                        (false, true) => true,
+                        (_, false) if i == entry.len() => return Some(key),
+                        _ => false,
+                    };
+
+                    if less {
+                        lo = mid + 1;
+                    } else {
+                        hi = mid;
+                    }
+                }
+
+                None
+            }
+
+            const fn lookup_discrim(a : ::avkeys_common::AvKeyDiscrim) -> Option<Self> {
                 match a {
-                    ::avkeys_common::AvKeyDiscrim::Str(s) => match s.as_bytes() {
-                        #(#lookup_str)*
-                        _ => None
-                    },
+                    ::avkeys_common::AvKeyDiscrim::Str(s) => Self::find_name(s.as_bytes()),
                     ::avkeys_common::AvKeyDiscrim::Int(i) => match i {
                         #(#lookup_ints)*
                         _ => None
@@ -516,18 +1007,44 @@ pub fn keycodes(body : TokenStream) -> TokenStream {
                 }
             } 
             
-            pub fn name(&self) -> Vec<&'static str> {
+            ///
+            /// This key's keycode, for use in `const` contexts
+            /// (same as its `Into<KeyCode>`).
+            ///
+            pub const fn code(self) -> ::avkeys_common::KeyCode {
+                match self {
+                    #(#ident_lookups),*
+                }
+            }
+
+            ///
+            /// Every name of this key: its primary name and aliases,
+            /// including character aliases (`"+"`).
+            ///
+            /// The primary name (the `Debug` form) comes first.
+            ///
+            pub fn name(&self) -> &'static [&'static str] {
                 match self {
                     #(#ident_names_str),*
                 }
             }
         }
 
+        ///
+        /// Converts a raw keycode (e.g. an evdev event's `code`) into its
+        /// primary variant, giving the code back if it has no key.
+        ///
+        impl TryFrom<::avkeys_common::KeyCode> for Key {
+            type Error = ::avkeys_common::KeyCode;
+
+            fn try_from(code : ::avkeys_common::KeyCode) -> Result<Self, Self::Error> {
+                Self::lookup_discrim(::avkeys_common::AvKeyDiscrim::Int(code)).ok_or(code)
+            }
+        }
+
         impl Into<::avkeys_common::KeyCode> for Key {
             fn into(self) -> ::avkeys_common::KeyCode {
-                match self {
-                    #(#ident_lookups),*
-                }
+                self.code()
             }
         }
 
@@ -0,0 +1,400 @@
+//!
+//! Loading keybind overrides from TOML config files.
+//!
+//! ### Format
+//! ```toml
+//! [keybinds]
+//! AvSearch  = "Logo+Space"
+//! SwitchTab = "Alt+{d}"
+//! ```
+//!
+
+use std::fmt;
+
+use crate::{accepts_parameters, parse_keybind, AvKey, ParseError, Registry};
+
+///
+/// Error from loading a keybind config (see [Registry::load_toml]).
+/// 
+#[derive(Debug, Clone)]
+pub enum ConfigError {
+    ///
+    /// The config file could not be read.
+    /// 
+    Io(String),
+
+    ///
+    /// Not valid TOML, or not in the expected shape.
+    /// 
+    Toml(String),
+
+    ///
+    /// No keybind is registered with this ID.
+    /// 
+    UnknownKeybind(String),
+
+    ///
+    /// The keybind string does not parse.
+    /// 
+    InvalidKeys { id : String, error : ParseError },
+
+    ///
+    /// The override has different key parameters than the keybind's callback expects.
+    /// 
+    ParameterMismatch { id : String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "Could not read config: {err}"),
+            ConfigError::Toml(err) => write!(f, "Invalid config: {err}"),
+            ConfigError::UnknownKeybind(id) => write!(f, "Unknown keybind `{id}`"),
+            ConfigError::InvalidKeys { id, error } => write!(f, "Invalid keys for `{id}`: {error}"),
+            ConfigError::ParameterMismatch { id } => write!(f, "Keys for `{id}` must have the same key parameters as its default"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+///
+/// Every error from loading a keybind config (see [Registry::load_toml]),
+/// in config order.
+/// 
+#[derive(Debug, Clone)]
+pub struct ConfigErrors(pub Vec<ConfigError>);
+
+impl ConfigErrors {
+    pub fn iter(&self) -> impl Iterator<Item = &ConfigError> {
+        self.0.iter()
+    }
+}
+
+impl From<ConfigError> for ConfigErrors {
+    fn from(err : ConfigError) -> Self {
+        Self(vec![err])
+    }
+}
+
+impl fmt::Display for ConfigErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, err) in self.0.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{err}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigErrors {}
+
+impl<S> Registry<S> {
+    ///
+    /// Applies a TOML config of keybind overrides (see the [module docs](crate::config)).
+    /// 
+    /// The config is the whole set of overrides: keybinds missing from it are
+    /// reset to their default keys.
+    /// 
+    /// Every valid override is applied, even if others are invalid: the errors
+    /// for all invalid ones are returned together, and their keybinds keep
+    /// their current keys. If the config isn't valid TOML, nothing is applied.
+    /// 
+    pub fn load_toml(&mut self, config : &str) -> Result<(), ConfigErrors> {
+        let table : toml::Table = config.parse()
+            .map_err(|err : toml::de::Error| ConfigError::Toml(err.to_string()))?;
+
+        let empty = toml::Table::new();
+        let keybinds = match table.get("keybinds") {
+            None => &empty,
+            Some(toml::Value::Table(t)) => t,
+            Some(_) => return Err(ConfigError::Toml("`keybinds` must be a table".to_string()).into()),
+        };
+
+        let mut overrides = vec![];
+        let mut errors = vec![];
+
+        for (id, value) in keybinds {
+            match self.resolve_override(id, value) {
+                Ok(o) => overrides.push(o),
+                Err(err) => errors.push(err),
+            }
+        }
+
+        let ids = self.iter()
+            .map(|k| k.id())
+            .filter(|id| !keybinds.contains_key(*id) || overrides.iter().any(|(o, _)| o == id))
+            .collect::<Vec<_>>();
+
+        for id in ids {
+            let keys = overrides.iter()
+                .position(|(o, _)| *o == id)
+                .map(|i| overrides.swap_remove(i).1);
+
+            // Checked by `resolve_override` already.
+            if self.set_override(id, keys).is_err() {
+                errors.push(ConfigError::ParameterMismatch { id : id.to_string() });
+            }
+        }
+
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(ConfigErrors(errors)),
+        }
+    }
+
+    ///
+    /// Validates a single `id = "keys"` entry of a config.
+    /// 
+    fn resolve_override(&self, id : &str, value : &toml::Value) -> Result<(&'static str, Vec<AvKey>), ConfigError> {
+        let keybind = self.get(id)
+            .ok_or_else(|| ConfigError::UnknownKeybind(id.to_string()))?;
+
+        let s = value.as_str()
+            .ok_or_else(|| ConfigError::Toml(format!("`keybinds.{id}` must be a string")))?;
+
+        let keys = parse_keybind(s)
+            .map_err(|error| ConfigError::InvalidKeys { id : id.to_string(), error })?;
+
+        let expected = keybind.keys()
+            .iter()
+            .filter_map(AvKey::key_parameter)
+            .collect::<Vec<_>>();
+
+        if !accepts_parameters(&expected, &keys) {
+            return Err(ConfigError::ParameterMismatch { id : id.to_string() });
+        }
+
+        Ok((keybind.id(), keys))
+    }
+}
+
+#[cfg(feature = "notify")]
+pub use watch::ConfigWatcher;
+
+#[cfg(feature = "notify")]
+mod watch {
+    use std::path::{Path, PathBuf};
+    use std::sync::mpsc::{channel, Receiver};
+
+    use notify::Watcher;
+
+    use crate::Registry;
+
+    use super::{ConfigError, ConfigErrors};
+
+    ///
+    /// Watches a config file for changes, re-applying it to a [Registry].
+    /// 
+    /// See [Registry::watch_config].
+    /// 
+    pub struct ConfigWatcher {
+        path      : PathBuf,
+        events    : Receiver<notify::Result<notify::Event>>,
+        on_reload : Box<dyn FnMut(Result<(), ConfigErrors>)>,
+        _watcher  : notify::RecommendedWatcher,
+    }
+
+    impl Registry {
+        ///
+        /// Watches a TOML config file (see [Registry::load_toml]).
+        /// 
+        /// The registry is not touched from the watcher's thread:
+        /// call [ConfigWatcher::poll] from your event loop to apply any changes.
+        /// `on_reload` is called with the result of every reload &mdash; keybinds
+        /// with invalid overrides keep their previous keys.
+        /// 
+        /// The file's directory is watched rather than the file itself,
+        /// so editors which save by replacing the file keep being followed.
+        /// 
+        /// ### Example
+        /// ```ignore
+        /// let mut watcher = Registry::watch_config("keybinds.toml", |res| {
+        ///     if let Err(err) = res {
+        ///         eprintln!("{err}");
+        ///     }
+        /// })?;
+        /// 
+        /// loop {
+        ///     watcher.poll(&mut registry);
+        ///     // ...
+        /// }
+        /// ```
+        /// 
+        pub fn watch_config<F>(path : impl AsRef<Path>, on_reload : F) -> notify::Result<ConfigWatcher>
+            where F : FnMut(Result<(), ConfigErrors>) + 'static
+        {
+            let (tx, events) = channel();
+            let mut watcher = notify::recommended_watcher(tx)?;
+
+            let dir = match path.as_ref().parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir,
+                _ => Path::new("."),
+            };
+            watcher.watch(dir, notify::RecursiveMode::NonRecursive)?;
+
+            Ok(ConfigWatcher {
+                path      : path.as_ref().to_path_buf(),
+                events,
+                on_reload : Box::new(on_reload),
+                _watcher  : watcher,
+            })
+        }
+    }
+
+    impl ConfigWatcher {
+        ///
+        /// Reloads the config if it changed (was written, replaced, or renamed to)
+        /// since the last poll, and exists.
+        /// 
+        /// Returns whether it was reloaded.
+        /// 
+        pub fn poll<S>(&mut self, registry : &mut Registry<S>) -> bool {
+            let name = self.path.file_name();
+            let changed = self.events.try_iter()
+                .filter_map(Result::ok)
+                .filter(|ev| !ev.kind.is_access())
+                .any(|ev| ev.paths.iter().any(|p| p.file_name() == name))
+                && self.path.exists();
+
+            if changed {
+                self.reload(registry);
+            }
+
+            changed
+        }
+
+        ///
+        /// Reloads the config now, whether it changed or not.
+        /// 
+        pub fn reload<S>(&mut self, registry : &mut Registry<S>) {
+            let result = std::fs::read_to_string(&self.path)
+                .map_err(|err| ConfigError::Io(err.to_string()).into())
+                .and_then(|config| registry.load_toml(&config));
+
+            (self.on_reload)(result);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::keybind::testing::TestKeybind;
+    use crate::{AvKey, AvKeyParameter, Registry};
+
+    use super::ConfigError;
+
+    fn registry() -> Registry {
+        let mut registry = Registry::new();
+        registry.register(TestKeybind::new("AvSearch", &[AvKey::Key(125), AvKey::Key(57)]));
+        registry.register(TestKeybind::new("SwitchTab", &[AvKey::Key(29), AvKey::Parameter(AvKeyParameter::DigitKey)]));
+        registry
+    }
+
+    fn codes(registry : &Registry, id : &str) -> Vec<Option<u32>> {
+        registry.get(id).unwrap().keys().iter().map(AvKey::key).collect()
+    }
+
+    #[test]
+    fn load_toml_resets_missing() {
+        let mut registry = registry();
+
+        registry.load_toml("[keybinds]\nAvSearch = \"Alt+Space\"\nSwitchTab = \"Alt+{d}\"").unwrap();
+        assert_eq!(codes(&registry, "AvSearch"), [Some(56), Some(57)]);
+        assert_eq!(codes(&registry, "SwitchTab"), [Some(56), None]);
+
+        registry.load_toml("[keybinds]\nSwitchTab = \"Alt+{d}\"").unwrap();
+        assert_eq!(codes(&registry, "AvSearch"), [Some(125), Some(57)]);
+
+        assert!(registry.load_toml("[keybinds").is_err());
+        assert_eq!(codes(&registry, "SwitchTab"), [Some(56), None]);
+    }
+
+    #[test]
+    fn load_toml_collects_errors() {
+        let mut registry = registry();
+        registry.register(TestKeybind::new("Screenshot", &[AvKey::Key(99)]));
+        registry.register(TestKeybind::new("Lock", &[AvKey::Key(125), AvKey::Key(38)]));
+        registry.set_override("Lock", Some(vec![AvKey::Key(29), AvKey::Key(38)])).unwrap();
+
+        let errors = registry.load_toml(
+            "[keybinds]\n\
+             AvSearch = \"Alt+Space\"\n\
+             SwitchTab = \"Alt+{f}\"\n\
+             Screenshot = \"Ctrl+P\"\n\
+             Lock = \"Logo+Nope\""
+        ).unwrap_err();
+
+        assert_eq!(errors.0.len(), 2);
+        assert!(errors.iter().any(|e| matches!(e, ConfigError::ParameterMismatch { id } if id == "SwitchTab")));
+        assert!(errors.iter().any(|e| matches!(e, ConfigError::InvalidKeys { id, .. } if id == "Lock")));
+
+        assert_eq!(codes(&registry, "AvSearch"), [Some(56), Some(57)]);
+        assert_eq!(codes(&registry, "Screenshot"), [Some(29), Some(25)]);
+        assert_eq!(codes(&registry, "SwitchTab"), [Some(29), None]);
+        assert_eq!(codes(&registry, "Lock"), [Some(29), Some(38)]);
+    }
+
+    #[test]
+    #[cfg(feature = "notify")]
+    fn watch_config_reload() {
+        use std::{cell::RefCell, rc::Rc};
+
+        let path = std::env::temp_dir().join(format!("avkeys-watch-{}.toml", std::process::id()));
+        std::fs::write(&path, "[keybinds]\nAvSearch = \"Alt+Space\"").unwrap();
+
+        let results = Rc::new(RefCell::new(vec![]));
+        let mut registry = registry();
+        let mut watcher = {
+            let results = results.clone();
+            Registry::watch_config(&path, move |res| results.borrow_mut().push(res.is_ok())).unwrap()
+        };
+
+        watcher.reload(&mut registry);
+        assert_eq!(codes(&registry, "AvSearch"), [Some(56), Some(57)]);
+
+        std::fs::write(&path, "[keybinds]\nAvSearch = \"Alt+Nope\"").unwrap();
+        watcher.reload(&mut registry);
+        assert_eq!(codes(&registry, "AvSearch"), [Some(56), Some(57)]);
+
+        assert_eq!(*results.borrow(), [true, false]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "notify")]
+    fn watch_config_poll() {
+        use std::time::{Duration, Instant};
+
+        let dir = std::env::temp_dir().join(format!("avkeys-poll-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("keybinds.toml");
+        std::fs::write(&path, "[keybinds]").unwrap();
+
+        let mut registry = registry();
+        let mut watcher = Registry::watch_config(&path, |res| assert!(res.is_ok())).unwrap();
+
+        // Polls until `AvSearch` was reloaded with `modifier`, or gives up.
+        let mut poll_until = |registry : &mut Registry, modifier| {
+            let start = Instant::now();
+            while !(watcher.poll(registry) && codes(registry, "AvSearch")[0] == Some(modifier)) {
+                assert!(start.elapsed() < Duration::from_secs(5), "config was never reloaded");
+                std::thread::sleep(Duration::from_millis(20));
+            }
+        };
+
+        std::fs::write(dir.join("other.toml"), "[keybinds]\nAvSearch = \"Ctrl+Space\"").unwrap();
+        std::fs::write(&path, "[keybinds]\nAvSearch = \"Alt+Space\"").unwrap();
+        poll_until(&mut registry, 56);
+
+        // Saved by renaming a new file over it, as many editors do.
+        let new = dir.join("keybinds.toml.new");
+        std::fs::write(&new, "[keybinds]\nAvSearch = \"Meta+Space\"").unwrap();
+        std::fs::rename(&new, &path).unwrap();
+        poll_until(&mut registry, 125);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,556 @@
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use crate::key::{either_side_mask, forbids, modifiers_fit, take_fixed};
+use crate::{is_modifier, modifier_mask, pressed_modifier_mask, AvKey, AvKeybind, KeyCode, TriggerMode};
+
+///
+/// How long a [sequence](AvKeybind::then) waits for its next step by default.
+/// 
+pub const DEFAULT_SEQUENCE_TIMEOUT : Duration = Duration::from_secs(1);
+
+///
+/// ### Keybind Dispatcher
+/// 
+/// Runs keybinds against a live key stream: [feed](Dispatcher::feed) it the
+/// keys currently held, every time they change.
+/// 
+/// A keybind fires when one of its keys is pressed and completes it, all of its
+/// keys then being held (key parameters taking any key they cover, e.g. `{d}` any digit).
+/// Other keys coming and going while it stays held don't fire it again.
+/// When several keybinds fire for the same keys, the longest one wins: pressing
+/// `Ctrl+Shift+A` runs a `Ctrl+Shift+A` keybind rather than a `Ctrl+A` one.
+/// [Exclusive](AvKeybind::exclusive_modifiers) keybinds only fire with exactly
+/// their modifiers held, as with [Registry::dispatch](crate::Registry::dispatch).
+/// 
+/// Keybinds [triggered](AvKeybind::trigger) on release fire instead
+/// when their keys stop being all held, and keybinds [requiring](AvKeybind::requires)
+/// capabilities which were not [provided](Dispatcher::provide) never fire.
+/// 
+/// ### Sequences
+/// A [sequence](AvKeybind::then) keybind (`Ctrl+K` then `Ctrl+C`) fires once each of
+/// its combinations was pressed in turn, as long as each came within the
+/// [timeout](Dispatcher::set_sequence_timeout) of the previous one. A non-modifier key
+/// outside the next combination abandons it. Plain keybinds win over sequences
+/// starting with the same keys (see [Registry::unreachable_sequences](crate::Registry::unreachable_sequences)).
+/// 
+/// ### Long-presses
+/// A keybind with a [hold duration](AvKeybind::hold_duration) (`#[AvKeybind(Power hold 2s)]`)
+/// fires once its keys were held that long, measured from the press completing it.
+/// The dispatcher only learns of time passing as it is fed, so feed it the same keys
+/// again (e.g. for key repeat events, or from a timer) for it to fire while they are held;
+/// it fires at the latest as they are released, if they were held long enough.
+/// 
+/// ### Anchors
+/// A keybind with an [anchor modifier](AvKeybind::anchor_modifier)
+/// (`#[AvKeybind(Alt+Tab, anchor = Alt)]`) repeats each time the rest of its keys
+/// are pressed again while the anchor stays held, and once the anchor is released,
+/// the [anchor release](Dispatcher::on_anchor_release) callback is called
+/// with its ID (e.g. to focus the window picked with `Alt+Tab`).
+/// 
+/// `S` is the state passed to the keybinds' callbacks (see [AvKeybind]).
+/// 
+pub struct Dispatcher<S = ()> {
+    keybinds          : Vec<Box<dyn AvKeybind<S>>>,
+    capabilities      : HashSet<String>,
+    last              : Vec<KeyCode>,
+    pending           : Vec<Pending>,
+    timeout           : Duration,
+    anchored          : Option<(usize, AvKey)>,
+    on_anchor_release : Option<OnAnchorRelease<S>>,
+    holding           : Option<Holding>,
+}
+
+///
+/// See [Dispatcher::on_anchor_release].
+/// 
+type OnAnchorRelease<S> = Box<dyn FnMut(&'static str, &mut S)>;
+
+///
+/// A sequence keybind partway through: the index of the keybind,
+/// how many of its [then](AvKeybind::then) steps were pressed,
+/// the parameters captured so far, and when its last step was pressed.
+/// 
+struct Pending {
+    keybind : usize,
+    step    : usize,
+    params  : Vec<usize>,
+    at      : Instant,
+}
+
+///
+/// A [long-press](Dispatcher#long-presses) keybind waiting for its keys to be held long enough:
+/// the index of the keybind, its parameters, and when its keys were pressed.
+/// 
+struct Holding {
+    keybind : usize,
+    params  : Vec<usize>,
+    since   : Instant,
+}
+
+impl<S> Default for Dispatcher<S> {
+    fn default() -> Self {
+        Self {
+            keybinds          : vec![],
+            capabilities      : HashSet::new(),
+            last              : vec![],
+            pending           : vec![],
+            timeout           : DEFAULT_SEQUENCE_TIMEOUT,
+            anchored          : None,
+            on_anchor_release : None,
+            holding           : None,
+        }
+    }
+}
+
+impl<S> Dispatcher<S> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Adds a keybind to this dispatcher.
+    /// 
+    pub fn register<K : AvKeybind<S> + 'static>(&mut self, keybind : K) {
+        self.keybinds.push(Box::new(keybind));
+    }
+
+    ///
+    /// Marks a capability (e.g. `"screenshot"`) as available,
+    /// enabling the keybinds which [require](AvKeybind::requires) it.
+    /// 
+    pub fn provide(&mut self, capability : impl Into<String>) {
+        self.capabilities.insert(capability.into());
+    }
+
+    ///
+    /// Sets how long a sequence keybind waits for each of its steps
+    /// ([DEFAULT_SEQUENCE_TIMEOUT] if not set).
+    /// 
+    pub fn set_sequence_timeout(&mut self, timeout : Duration) {
+        self.timeout = timeout;
+    }
+
+    ///
+    /// Sets what to do when the anchor modifier of the last
+    /// [anchored](Dispatcher#anchors) keybind run is released,
+    /// given that keybind's ID and the state.
+    /// 
+    pub fn on_anchor_release<F>(&mut self, on_release : F)
+        where F : FnMut(&'static str, &mut S) + 'static
+    {
+        self.on_anchor_release = Some(Box::new(on_release));
+    }
+
+    ///
+    /// Runs the longest keybind whose keys are all in `pressed`, one of them
+    /// not being in the last keys fed (or for release keybinds, were all in the last keys fed
+    /// but no longer are), with the values of its key parameters.
+    /// 
+    /// Feeding the same keys twice in a row (e.g. for key repeat events)
+    /// does nothing the second time, but for [long-press](Dispatcher#long-presses)
+    /// keybinds; keybinds of equal length are tried in registration order.
+    /// 
+    /// Returns the [ID](AvKeybind::id) of the keybind run, if any
+    /// (the last one, if a long-press keybind fired first).
+    /// 
+    pub fn feed(&mut self, pressed : &[KeyCode], state : &mut S) -> Option<&'static str> {
+        self.feed_at(pressed, state, Instant::now())
+    }
+
+    ///
+    /// Like [feed](Dispatcher::feed), with the time the keys changed
+    /// (e.g. from the input event) for [sequences](Dispatcher#sequences).
+    /// 
+    pub fn feed_at(&mut self, pressed : &[KeyCode], state : &mut S, now : Instant) -> Option<&'static str> {
+        let held = self.run_held(state, now);
+
+        let mut sorted = pressed.to_vec();
+        sorted.sort_unstable();
+        sorted.dedup();
+
+        if sorted == self.last {
+            return held;
+        }
+        let last = std::mem::replace(&mut self.last, sorted);
+
+        self.feed_changed(pressed, &last, state, now).or(held)
+    }
+
+    ///
+    /// Runs the [long-press](Dispatcher#long-presses) keybind
+    /// waiting for its keys, if they were held long enough by `now`.
+    /// 
+    fn run_held(&mut self, state : &mut S, now : Instant) -> Option<&'static str> {
+        let holding = self.holding.take()?;
+        let keybind = &self.keybinds[holding.keybind];
+        let hold = keybind.hold_duration().unwrap_or_default();
+
+        if now.saturating_duration_since(holding.since) < hold {
+            self.holding = Some(holding);
+            return None;
+        }
+
+        keybind.run(state, holding.params);
+        Some(keybind.id())
+    }
+
+    ///
+    /// Runs what the keys changing from `last` to `pressed` fires.
+    /// 
+    fn feed_changed(&mut self, pressed : &[KeyCode], last : &[KeyCode], state : &mut S, now : Instant) -> Option<&'static str> {
+        if let Some((keybind, anchor)) = self.anchored {
+            if !pressed.iter().any(|code| AvKey::Key(*code).matches(&anchor)) {
+                self.anchored = None;
+                if let Some(on_release) = self.on_anchor_release.as_mut() {
+                    on_release(self.keybinds[keybind].id(), state);
+                }
+            }
+        }
+
+        if let Some(i) = self.advance_sequences(pressed, now) {
+            let Pending { keybind, params, .. } = self.pending.swap_remove(i);
+            self.pending.clear();
+
+            let keybind = &self.keybinds[keybind];
+            keybind.run(state, params);
+            return Some(keybind.id());
+        }
+
+        let available = |k : &dyn AvKeybind<S>| k.requires()
+            .iter()
+            .all(|c| self.capabilities.contains(*c));
+
+        let just_pressed = pressed.iter()
+            .filter(|code| !last.contains(code))
+            .collect::<Vec<_>>();
+
+        let press = |k : &dyn AvKeybind<S>| held_params(k.keys(), k.exclusive_modifiers(), pressed)
+            .filter(|_| just_pressed.iter().any(|code| k.keys().iter().any(|key| AvKey::Key(**code).matches(key))));
+        let longest = |(i, _) : &(usize, Vec<usize>)| self.keybinds[*i].keys()
+            .iter()
+            .filter(|k| k.not_held().is_none())
+            .count();
+
+        // Long-presses wait for as long as their keys stay held.
+        let still_held = self.holding.as_ref().is_some_and(|h| {
+            let keybind = &self.keybinds[h.keybind];
+            held_params(keybind.keys(), keybind.exclusive_modifiers(), pressed).is_some()
+        });
+        if !still_held {
+            self.holding = self.keybinds.iter()
+                .enumerate()
+                .filter(|(_, k)| available(k.as_ref()) && k.then().is_empty() && k.trigger() == TriggerMode::Press)
+                .filter(|(_, k)| k.hold_duration().is_some())
+                .filter_map(|(i, k)| press(k.as_ref()).map(|params| (i, params)))
+                .rev()
+                .max_by_key(longest)
+                .map(|(keybind, params)| Holding { keybind, params, since : now });
+        }
+
+        let fired = self.keybinds.iter()
+            .enumerate()
+            .filter(|(_, k)| available(k.as_ref()) && k.then().is_empty())
+            .filter_map(|(i, k)| match k.trigger() {
+                TriggerMode::Press if k.hold_duration().is_some() => None,
+                TriggerMode::Press => press(k.as_ref()),
+                TriggerMode::Release => {
+                    let held = |pressed| held_params(k.keys(), k.exclusive_modifiers(), pressed);
+                    held(pressed)
+                        .is_none()
+                        .then(|| held(last))
+                        .flatten()
+                },
+            }.map(|params| (i, params)))
+            .rev()
+            .max_by_key(longest);
+
+        if let Some((i, params)) = fired {
+            self.pending.clear();
+
+            let keybind = &self.keybinds[i];
+            if let Some(anchor) = keybind.anchor_modifier() {
+                // The anchor as the keybind has it, so `Alt` stays held on either side.
+                let anchor = keybind.keys().iter()
+                    .copied()
+                    .find(|k| AvKey::Key(anchor).matches(k))
+                    .unwrap_or(AvKey::Key(anchor));
+                self.anchored = Some((i, anchor));
+            }
+
+            keybind.run(state, params);
+            return Some(keybind.id());
+        }
+
+        let started = self.keybinds.iter()
+            .enumerate()
+            .filter(|(_, k)| available(k.as_ref()) && !k.then().is_empty())
+            .filter_map(|(i, k)| held_params(k.keys(), k.exclusive_modifiers(), pressed)
+                .map(|params| Pending { keybind : i, step : 0, params, at : now }))
+            .collect::<Vec<_>>();
+
+        if !started.is_empty() {
+            self.pending = started;
+        }
+
+        None
+    }
+
+    ///
+    /// Moves pending sequences on with the newly pressed keys, dropping
+    /// those which timed out or were abandoned.
+    /// 
+    /// Returns the index (in `pending`) of a sequence this completes, if any.
+    /// 
+    fn advance_sequences(&mut self, pressed : &[KeyCode], now : Instant) -> Option<usize> {
+        let keybinds = &self.keybinds;
+        let timeout = self.timeout;
+
+        self.pending.retain(|p| now.saturating_duration_since(p.at) <= timeout);
+        self.pending.retain(|p| {
+            let next = keybinds[p.keybind].then()[p.step].keys();
+            pressed.iter()
+                .filter(|code| !is_modifier(**code))
+                .all(|code| next.iter().any(|k| AvKey::Key(*code).matches(k)))
+        });
+
+        for p in self.pending.iter_mut() {
+            let keybind = &keybinds[p.keybind];
+            if let Some(params) = held_params(keybind.then()[p.step].keys(), keybind.exclusive_modifiers(), pressed) {
+                p.params.extend(params);
+                p.step += 1;
+                p.at = now;
+            }
+        }
+
+        self.pending.iter()
+            .position(|p| p.step == keybinds[p.keybind].then().len())
+    }
+}
+
+///
+/// Whether all of a combination's keys are held (and none of its
+/// [not held](crate::AvKey::NotHeld) ones, on either side), with its parameters' values.
+/// 
+/// When `exclusive`, no modifiers but its own may be held (see [AvKeybind::exclusive_modifiers]).
+/// 
+fn held_params(keys : &[AvKey], exclusive : bool, pressed : &[KeyCode]) -> Option<Vec<usize>> {
+    let mask = pressed_modifier_mask(pressed);
+    if !modifiers_fit(modifier_mask(keys), either_side_mask(keys), mask, exclusive) {
+        return None;
+    }
+
+    if pressed.iter().any(|p| forbids(keys, *p)) {
+        return None;
+    }
+
+    let mut remaining = pressed.to_vec();
+    take_fixed(keys, &mut remaining)?;
+
+    keys.iter()
+        .filter_map(|k| k.key_parameter())
+        .map(|param| {
+            let i = remaining.iter().position(|p| param.contains(*p))?;
+            param.value(remaining.swap_remove(i))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::keybind::testing::TestKeybind;
+    use crate::{AvKey, AvKeyParameter, KeyCombo, TriggerMode};
+
+    use super::Dispatcher;
+
+    #[test]
+    fn longest_match_wins() {
+        let mut dispatcher = Dispatcher::new();
+        dispatcher.register(TestKeybind::recording("SelectAll", &[AvKey::Key(29), AvKey::Key(30)]));
+        dispatcher.register(TestKeybind::recording("SelectNone", &[AvKey::Key(29), AvKey::Key(42), AvKey::Key(30)]));
+
+        let mut runs = vec![];
+        assert_eq!(dispatcher.feed(&[29, 30], &mut runs), Some("SelectAll"));
+        assert_eq!(dispatcher.feed(&[29, 30], &mut runs), None);
+        assert_eq!(dispatcher.feed(&[29, 42, 30], &mut runs), Some("SelectNone"));
+        assert_eq!(dispatcher.feed(&[29], &mut runs), None);
+        assert_eq!(runs.len(), 2);
+    }
+
+    #[test]
+    fn fires_once_while_held() {
+        let mut dispatcher = Dispatcher::new();
+        dispatcher.register(TestKeybind::recording("SelectAll", &[AvKey::Key(29), AvKey::Key(30)]));
+        dispatcher.register(TestKeybind::recording("SelectNone", &[AvKey::Key(29), AvKey::Key(42), AvKey::Key(30)]));
+
+        let mut runs = vec![];
+        assert_eq!(dispatcher.feed(&[29, 30], &mut runs), Some("SelectAll"));
+
+        // Unrelated keys coming and going.
+        assert_eq!(dispatcher.feed(&[29, 30, 48], &mut runs), None);
+        assert_eq!(dispatcher.feed(&[29, 30], &mut runs), None);
+
+        // Letting go of Shift leaves `Ctrl+A` held, but A wasn't pressed again.
+        assert_eq!(dispatcher.feed(&[29, 42, 30], &mut runs), Some("SelectNone"));
+        assert_eq!(dispatcher.feed(&[29, 30], &mut runs), None);
+
+        dispatcher.feed(&[29], &mut runs);
+        assert_eq!(dispatcher.feed(&[29, 30], &mut runs), Some("SelectAll"));
+        assert_eq!(runs.len(), 3);
+    }
+
+    #[test]
+    fn exclusive_modifiers() {
+        let mut dispatcher = Dispatcher::new();
+        dispatcher.register(TestKeybind {
+            exclusive : true,
+            ..TestKeybind::recording("Copy", &[AvKey::EitherSide(29), AvKey::Key(46)])
+        });
+        dispatcher.register(TestKeybind::recording("Paste", &[AvKey::Key(29), AvKey::Key(47)]));
+
+        let mut runs = vec![];
+        assert_eq!(dispatcher.feed(&[29, 42, 46], &mut runs), None);
+        assert_eq!(dispatcher.feed(&[97, 46], &mut runs), Some("Copy"));
+
+        // Only exclusive keybinds turn extra modifiers away.
+        assert_eq!(dispatcher.feed(&[29, 42, 47], &mut runs), Some("Paste"));
+    }
+
+    #[test]
+    fn collects_parameters() {
+        let mut dispatcher = Dispatcher::new();
+        dispatcher.register(TestKeybind::recording("SwitchTab", &[AvKey::Key(29), AvKey::Parameter(AvKeyParameter::DigitKey)]));
+        dispatcher.register(TestKeybind::recording("Tab1", &[AvKey::Key(29), AvKey::Key(2), AvKey::NotHeld(42)]));
+
+        let mut runs = vec![];
+        dispatcher.feed(&[29, 6], &mut runs);
+        dispatcher.feed(&[29, 42, 2], &mut runs);
+        dispatcher.feed(&[29], &mut runs);
+        dispatcher.feed(&[29, 54, 2], &mut runs);
+
+        assert_eq!(runs, vec![("SwitchTab", vec![5]), ("SwitchTab", vec![1]), ("SwitchTab", vec![1])]);
+    }
+
+    #[test]
+    fn fires_on_release() {
+        let mut dispatcher = Dispatcher::new();
+        dispatcher.register(TestKeybind {
+            trigger : TriggerMode::Release,
+            ..TestKeybind::recording("PushToTalk", &[AvKey::Key(125), AvKey::Key(47)])
+        });
+
+        let mut runs = vec![];
+        assert_eq!(dispatcher.feed(&[125, 47], &mut runs), None);
+        assert_eq!(dispatcher.feed(&[125, 47, 42], &mut runs), None);
+        assert_eq!(dispatcher.feed(&[125], &mut runs), Some("PushToTalk"));
+        assert_eq!(dispatcher.feed(&[], &mut runs), None);
+    }
+
+    #[test]
+    fn skips_missing_capabilities() {
+        let mut dispatcher = Dispatcher::new();
+        dispatcher.register(TestKeybind {
+            requires : &["screenshot", "clipboard"],
+            ..TestKeybind::recording("Screenshot", &[AvKey::Key(99)])
+        });
+
+        let mut runs = vec![];
+        dispatcher.provide("screenshot");
+        assert_eq!(dispatcher.feed(&[99], &mut runs), None);
+
+        dispatcher.feed(&[], &mut runs);
+        dispatcher.provide("clipboard");
+        assert_eq!(dispatcher.feed(&[99], &mut runs), Some("Screenshot"));
+    }
+
+    #[test]
+    fn fires_sequences() {
+        let mut dispatcher = Dispatcher::new();
+        dispatcher.register(TestKeybind {
+            then : vec![KeyCombo::new(vec![AvKey::Key(34)]), KeyCombo::new(vec![AvKey::Key(34)])],
+            ..TestKeybind::recording("GoToTop", &[AvKey::Key(125)])
+        });
+        dispatcher.register(TestKeybind {
+            then : vec![KeyCombo::new(vec![AvKey::Key(29), AvKey::Key(46)])],
+            ..TestKeybind::recording("Comment", &[AvKey::Key(29), AvKey::Key(37)])
+        });
+
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut runs = vec![];
+
+        for (pressed, ms) in [(&[125][..], 0), (&[], 100), (&[34], 200), (&[], 300)] {
+            assert_eq!(dispatcher.feed_at(pressed, &mut runs, at(ms)), None);
+        }
+        assert_eq!(dispatcher.feed_at(&[34], &mut runs, at(400)), Some("GoToTop"));
+
+        // Too slow.
+        dispatcher.feed_at(&[125], &mut runs, at(1000));
+        dispatcher.feed_at(&[34], &mut runs, at(1500));
+        dispatcher.feed_at(&[], &mut runs, at(1600));
+        assert_eq!(dispatcher.feed_at(&[34], &mut runs, at(3000)), None);
+
+        // Abandoned by another key.
+        dispatcher.feed_at(&[125], &mut runs, at(4000));
+        dispatcher.feed_at(&[30], &mut runs, at(4100));
+        dispatcher.feed_at(&[34], &mut runs, at(4200));
+        assert_eq!(dispatcher.feed_at(&[], &mut runs, at(4300)), None);
+        assert_eq!(dispatcher.feed_at(&[34], &mut runs, at(4400)), None);
+
+        dispatcher.feed_at(&[29, 37], &mut runs, at(5000));
+        dispatcher.feed_at(&[29], &mut runs, at(5100));
+        assert_eq!(dispatcher.feed_at(&[29, 46], &mut runs, at(5200)), Some("Comment"));
+
+        assert_eq!(runs, vec![("GoToTop", vec![]), ("Comment", vec![])]);
+    }
+
+    #[test]
+    fn fires_after_hold() {
+        let mut dispatcher = Dispatcher::new();
+        dispatcher.register(TestKeybind {
+            hold : Some(Duration::from_secs(2)),
+            ..TestKeybind::recording("PowerOff", &[AvKey::Key(116)])
+        });
+
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut runs = vec![];
+
+        // Key repeats until held long enough, then only once.
+        assert_eq!(dispatcher.feed_at(&[116], &mut runs, at(0)), None);
+        assert_eq!(dispatcher.feed_at(&[116], &mut runs, at(1999)), None);
+        assert_eq!(dispatcher.feed_at(&[116], &mut runs, at(2000)), Some("PowerOff"));
+        assert_eq!(dispatcher.feed_at(&[116], &mut runs, at(3000)), None);
+
+        // Released too early.
+        dispatcher.feed_at(&[], &mut runs, at(4000));
+        dispatcher.feed_at(&[116], &mut runs, at(5000));
+        assert_eq!(dispatcher.feed_at(&[], &mut runs, at(6000)), None);
+        assert_eq!(dispatcher.feed_at(&[], &mut runs, at(8000)), None);
+
+        // Held long enough, as told by its release.
+        dispatcher.feed_at(&[116], &mut runs, at(9000));
+        assert_eq!(dispatcher.feed_at(&[116, 30], &mut runs, at(10000)), None);
+        assert_eq!(dispatcher.feed_at(&[], &mut runs, at(11000)), Some("PowerOff"));
+
+        assert_eq!(runs.len(), 2);
+    }
+
+    #[test]
+    fn sequence_timeout() {
+        let mut dispatcher = Dispatcher::new();
+        dispatcher.register(TestKeybind {
+            then : vec![KeyCombo::new(vec![AvKey::Key(34)])],
+            ..TestKeybind::recording("GoToTop", &[AvKey::Key(34)])
+        });
+        dispatcher.set_sequence_timeout(Duration::from_secs(5));
+
+        let start = Instant::now();
+        let mut runs = vec![];
+        dispatcher.feed_at(&[34], &mut runs, start);
+        dispatcher.feed_at(&[], &mut runs, start + Duration::from_secs(1));
+        assert_eq!(dispatcher.feed_at(&[34], &mut runs, start + Duration::from_secs(4)), Some("GoToTop"));
+    }
+}
@@ -1,3 +1,11 @@
+use std::collections::HashMap;
+
+use crate::{Key, Keymap, Layout, UnknownKey};
+
+pub use avkeys_common::modifiers::is_modifier;
+pub(crate) use avkeys_common::modifiers::either_side_code;
+use avkeys_common::modifiers::{either_side_name, other_side, EITHER_SIDE_NAMES, MODIFIER_KEYS, MODIFIER_SIDES};
+
 
 
 
@@ -11,22 +19,60 @@
 /// 
 pub type KeyCode = u32;
 
+///
+/// Event type of key events (`EV_KEY`) in the Linux input subsystem.
+/// 
+pub const EV_KEY : u16 = 1;
+
+///
+/// State of a key in an `EV_KEY` input event,
+/// with the event's numeric `value`.
+/// 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyState {
+    Released = 0,
+    Pressed  = 1,
+    ///
+    /// Auto-repeat while held down.
+    /// 
+    Repeated = 2,
+}
+
 ///
 /// ### Keyboard Keys
 /// 
 /// The `AvKey` enum represents a keyboard key in a key combination.
 /// 
 /// It supports:
-/// * a fixed key, or
-/// * a colllection of keys. 
+/// * a fixed key,
+/// * a modifier on either side of the keyboard,
+/// * a colllection of keys, or
+/// * a key which must *not* be held. 
 /// 
-#[derive(Debug, Clone, Copy)]
+/// Equality (and hashing) is exact: `Key(6)` is not equal to `Parameter(DigitKey)`,
+/// even though the parameter covers it, so combinations can key a `HashMap`.
+/// Use [AvKey::matches] to check key parameter membership.
+/// 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum AvKey {
     ///
     /// A fixed physical key, using linux' keycodes.
     /// 
     Key(KeyCode),
 
+    ///
+    /// A modifier on either side of the keyboard, by its left-side keycode:
+    /// `EitherSide(29)` is held when `LeftCtrl` or `RightCtrl` is.
+    /// 
+    /// This is what the side-less names `Ctrl`, `Shift`, `Alt`, and `Meta`
+    /// (also `Logo` and `Win`) stand for, so `Ctrl+C` fires for either Ctrl key;
+    /// `LeftCtrl` and `RightCtrl` are still only that physical key.
+    /// 
+    /// Like a fixed key, it has a [keycode](AvKey::key) (the left side's),
+    /// and takes up one of the pressed keys when [matching](matches).
+    /// 
+    EitherSide(KeyCode),
+
     ///
     /// Represents a collection of related keys,
     /// to support one keybind implementation for multiple
@@ -34,7 +80,17 @@ pub enum AvKey {
     /// 
     /// See [AvKeyParameter] for more information.
     /// 
-    Parameter(AvKeyParameter)
+    Parameter(AvKeyParameter),
+
+    ///
+    /// A physical key which must *not* be held for the combination to match,
+    /// written `!Shift` when declaring keybinds. For modifiers, neither side may
+    /// be held (given by either side's keycode).
+    /// 
+    /// This disambiguates overlapping keybinds, e.g.
+    /// `Ctrl+A+!Shift` never fires for `Ctrl+Shift+A`.
+    /// 
+    NotHeld(KeyCode),
 }
 
 impl AvKey {
@@ -54,14 +110,15 @@ impl AvKey {
     /// 
     pub fn key_parameter(&self) -> Option<AvKeyParameter> {
         match self {
-            AvKey::Key(_) => None,
+            AvKey::Key(_) | AvKey::EitherSide(_) | AvKey::NotHeld(_) => None,
             AvKey::Parameter(p) => Some(p.clone()),
         }
     }
 
     ///
     /// Returns an option of wether this [AvKey] is
-    /// a real key (has a keycode).
+    /// a real key (has a keycode): for [either side](AvKey::EitherSide)
+    /// modifiers, the left side's keycode.
     ///
     /// ### Example
     /// ```ignore
@@ -75,8 +132,19 @@ impl AvKey {
     ///
     pub fn key(&self) -> Option<KeyCode> {
         match self {
-            AvKey::Key(k) => Some(*k),
-            AvKey::Parameter(_) => None,
+            AvKey::Key(k) | AvKey::EitherSide(k) => Some(*k),
+            AvKey::Parameter(_) | AvKey::NotHeld(_) => None,
+        }
+    }
+
+    ///
+    /// Returns an option of whether this [AvKey] is
+    /// a key which must not be held ([AvKey::NotHeld]).
+    /// 
+    pub fn not_held(&self) -> Option<KeyCode> {
+        match self {
+            AvKey::NotHeld(k) => Some(*k),
+            AvKey::Key(_) | AvKey::EitherSide(_) | AvKey::Parameter(_) => None,
         }
     }
 }
@@ -89,8 +157,13 @@ impl AvKey {
 /// ### Types
 /// * [Digit Keys](parameters::DigitKey) (`0`..=`9`) `{d}` 
 /// * [Function Keys](parameters::FunctionKey) (`F1`..=`F12`) `{f}` 
+/// * [Letter Keys](parameters::LetterKey) (`A`..=`Z`) `{a}` 
+/// * [Printable Keys](parameters::PrintableKey) (`A`, `1`, `;`, ...) `{printable}` 
+/// * [Custom](AvKeyParameter::Custom) ones, declared with [keyparameters!](crate::keyparameters)
 /// 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Ordered as listed above, custom ones last (by short code).
+/// 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum AvKeyParameter {
     ///
     /// ### Key Parameter `{d}` &mdash; Digit Key
@@ -144,14 +217,234 @@ pub enum AvKeyParameter {
     /// }
     /// ```
     /// 
-    FunctionKey
+    FunctionKey,
+
+    ///
+    /// ### Key Parameter `{a}` &mdash; Letter Key
+    /// Used in place for any letter key (`A` to `Z`, inclusive).
+    /// 
+    /// The value passed to the callback is the letter's 0-based index
+    /// in the alphabet (`A` is `0`, `Z` is `25`). Keys are matched by position,
+    /// so these are the letters of US QWERTY.
+    /// 
+    /// #### Syntax
+    /// When declaring keybinds, use the `{a}` syntax to specify
+    /// this key parameter.
+    /// 
+    /// #### Example
+    /// ```ignore
+    /// use av_macros::AvKeybind;
+    /// use Navda::AvKeybind;
+    /// 
+    /// ///
+    /// /// Jump to the mark named by the letter.
+    /// ///
+    /// #[AvKeybind(Logo+{a})]
+    /// pub fn JumpToMark(state : &mut (...), letter : usize) {
+    ///     state.jump_to(state.marks[letter]);
+    /// }
+    /// ```
+    /// 
+    LetterKey,
+
+    ///
+    /// ### Key Parameter `{printable}` &mdash; Printable Key
+    /// Used in place for any key in the main alphanumeric block
+    /// (letters, digits, punctuation, and space).
+    /// 
+    /// The value passed to the callback is the (lowercase/unshifted) character
+    /// the key types on US QWERTY, e.g. `'a'` for the `A` key, when declared as `char`;
+    /// or its code point when declared as `usize`. Keys are matched by position,
+    /// so on other layouts, convert the keycode with [Layout::char_of] instead.
+    /// 
+    /// #### Specificity
+    /// This parameter overlaps with almost every other key parameter,
+    /// and with most fixed non-modifier keys: `Logo+{printable}` also
+    /// matches everything `Logo+{d}` and `Logo+A` would.
+    /// 
+    /// Keep `{printable}` keybinds behind a modifier that nothing else uses,
+    /// or make sure the dispatcher prefers the more specific keybind
+    /// (fixed keys over `{d}`/`{f}`, and those over `{printable}`).
+    /// 
+    /// #### Syntax
+    /// When declaring keybinds, use the `{printable}` syntax to specify
+    /// this key parameter.
+    /// 
+    /// #### Example
+    /// ```ignore
+    /// use av_macros::AvKeybind;
+    /// use Navda::AvKeybind;
+    /// 
+    /// ///
+    /// /// Start a leader-key sequence with the typed character.
+    /// ///
+    /// #[AvKeybind(Logo+{printable})]
+    /// pub fn Leader(state : &mut (...), ch : char) {
+    ///     state.leader.push(ch);
+    /// }
+    /// ```
+    /// 
+    PrintableKey,
+
+    ///
+    /// ### Custom Key Parameter
+    /// A key parameter declared downstream with [keyparameters!](crate::keyparameters),
+    /// e.g. `{kp}` for keypad digits.
+    /// 
+    /// Its [keys](AvKeyParameter::keys) are the ones listed in its declaration,
+    /// and the [value](AvKeyParameter::value) of a key is its position in that list.
+    /// 
+    /// Custom key parameters are resolved by name when compiling keybinds, so
+    /// they aren't known to the runtime parsers ([parse_keybind], `FromStr`, serde).
+    /// 
+    Custom(CustomKeyParameter),
+}
+
+///
+/// Definition of a [custom key parameter](AvKeyParameter::Custom):
+/// its short code, and the keys it covers.
+/// 
+/// Compared, ordered (and hashed) by short code only.
+/// 
+#[derive(Debug, Clone, Copy)]
+pub struct CustomKeyParameter {
+    code : &'static str,
+    keys : &'static [KeyCode],
+}
+
+impl CustomKeyParameter {
+    pub const fn new(code : &'static str, keys : &'static [KeyCode]) -> Self {
+        Self { code, keys }
+    }
+
+    ///
+    /// Short code, as written in keybinds without the braces (`kp`).
+    /// 
+    pub const fn code(&self) -> &'static str {
+        self.code
+    }
+
+    pub const fn keys(&self) -> &'static [KeyCode] {
+        self.keys
+    }
+}
+
+impl PartialEq for CustomKeyParameter {
+    fn eq(&self, other : &Self) -> bool {
+        self.code == other.code
+    }
+}
+
+impl Eq for CustomKeyParameter {}
+
+impl PartialOrd for CustomKeyParameter {
+    fn partial_cmp(&self, other : &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
-// Number Keys:                     0   1  2  3  4  5  6  7  8   9    
-const DIGIT_KEYS : [KeyCode; 10] = [11, 2, 3, 4, 5, 6, 7, 8, 9, 10];
-// Function Keys:                 F..  1   2   3   4   5   6   7   8   9   10  11  12
-const FUNCTION_KEYS : [KeyCode; 12] = [59, 60, 61, 62, 63, 64, 65, 66, 67, 68, 87, 88];
+impl Ord for CustomKeyParameter {
+    fn cmp(&self, other : &Self) -> std::cmp::Ordering {
+        self.code.cmp(other.code)
+    }
+}
+
+impl std::hash::Hash for CustomKeyParameter {
+    fn hash<H : std::hash::Hasher>(&self, state : &mut H) {
+        self.code.hash(state)
+    }
+}
+
+///
+/// Declares [custom key parameters](AvKeyParameter::Custom), for use
+/// in [#\[AvKeybind\]](avkeys_macros::AvKeybind) like the built-in ones.
+/// 
+/// Each declaration gives the short code used in keybinds, the name of the
+/// generated `AvKeyParameter` constant, and the keys covered. A key's
+/// [value](AvKeyParameter::value) is its position in the list.
+/// 
+/// The short code is also declared, as a hidden constant: keybinds list it
+/// (`params = [kp]`) and resolve `{kp}` to it by name, so it must be in scope
+/// where they are declared.
+/// Short codes of built-in key parameters (`d`, `f`, ...) can't be redefined.
+/// 
+/// ### Example
+/// ```ignore
+/// keyparameters! {
+///     /// Keypad digits, valued `0` to `9`.
+///     kp  => KeyPadDigit [82, 79, 80, 81, 75, 76, 77, 71, 72, 73],
+///     /// Arrow keys: up, left, right, down.
+///     nav => ArrowKey [103, 105, 106, 108],
+/// }
+/// 
+/// #[AvKeybind(Logo+{kp}, params = [kp])]
+/// pub fn SwitchWorkspace(state : &mut (), workspace : usize) { ... }
+/// ```
+/// 
+#[macro_export]
+macro_rules! keyparameters {
+    ($( $(#[$attr:meta])* $code:ident => $name:ident [$($key:expr),* $(,)?] ),* $(,)?) => {
+        $(
+            $(#[$attr])*
+            #[allow(non_upper_case_globals)]
+            pub const $name : $crate::AvKeyParameter = $crate::AvKeyParameter::Custom(
+                $crate::CustomKeyParameter::new(stringify!($code), &[$($key),*])
+            );
+
+            #[doc(hidden)]
+            #[allow(non_upper_case_globals)]
+            pub const $code : $crate::AvKeyParameter = $name;
+        )*
+    };
+}
+
+// Number Keys, `0` to `9`, tagged `#[param(digit = ..)]` in the `keycodes!` table.
+const DIGIT_KEYS : [KeyCode; 10] = Key::DIGIT_KEYS;
+// Function Keys, `F1` to `F12`, tagged `#[param(function = ..)]`.
+const FUNCTION_KEYS : [KeyCode; 12] = Key::FUNCTION_KEYS;
+// Letter Keys, `A` to `Z`, tagged `#[param(letter = ..)]`.
+const LETTER_KEYS : [KeyCode; 26] = Key::LETTER_KEYS;
+// Printable Keys: the main alphanumeric block and space, in keycode order.
+pub(crate) const PRINTABLE_KEYS : [KeyCode; 48] = [
+     2,  3,  4,  5,  6,  7,  8,  9, 10, 11, 12, 13,         //  1 .. 0  -  =
+    16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27,         //  q .. p  [  ]
+    30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41,         //  a .. l  ;  '  `
+    43, 44, 45, 46, 47, 48, 49, 50, 51, 52, 53, 57,         //  \  z .. m  ,  .  /  ␣
+];
+
+///
+/// Returns the (unshifted) ASCII character typed by a key
+/// in the US QWERTY layout, if it types one.
+/// 
+/// This is *logical*, so layout-dependent:
+/// use [Layout::char_of] for other layouts.
+/// 
+/// ### Example
+/// ```ignore
+/// assert_eq!(ascii_char(30), Some('a'));
+/// assert_eq!(ascii_char(29), None); // LeftCtrl
+/// ```
+/// 
+pub const fn ascii_char(key : KeyCode) -> Option<char> {
+    Layout::UsQwerty.char_of(key)
+}
 
+///
+/// Whether a key is in the main alphanumeric block (or is space),
+/// so types a character in any supported [Layout].
+/// 
+/// This is *physical*, so layout-independent.
+/// 
+pub const fn is_printable(key : KeyCode) -> bool {
+    let mut i = 0;
+    while i < PRINTABLE_KEYS.len() {
+        if PRINTABLE_KEYS[i] == key {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
 
 impl AvKeyParameter {
     ///
@@ -161,6 +454,39 @@ impl AvKeyParameter {
         match self {
             AvKeyParameter::DigitKey => &DIGIT_KEYS,
             AvKeyParameter::FunctionKey => &FUNCTION_KEYS,
+            AvKeyParameter::LetterKey => &LETTER_KEYS,
+            AvKeyParameter::PrintableKey => &PRINTABLE_KEYS,
+            AvKeyParameter::Custom(p) => p.keys(),
+        }
+    }
+
+    ///
+    /// Returns whether a key is in this KeyParameter's bounds.
+    /// 
+    pub fn contains(&self, key : KeyCode) -> bool {
+        self.matches_code(key)
+    }
+
+    ///
+    /// Returns whether a key is in this KeyParameter's bounds,
+    /// by range checks rather than searching [keys](AvKeyParameter::keys).
+    /// 
+    pub const fn matches_code(&self, code : KeyCode) -> bool {
+        match self {
+            AvKeyParameter::DigitKey => matches!(code, 2..=11),
+            AvKeyParameter::FunctionKey => matches!(code, 59..=68 | 87 | 88),
+            AvKeyParameter::LetterKey => matches!(code, 16..=25 | 30..=38 | 44..=50),
+            AvKeyParameter::PrintableKey => is_printable(code),
+            AvKeyParameter::Custom(p) => {
+                let mut i = 0;
+                while i < p.keys.len() {
+                    if p.keys[i] == code {
+                        return true;
+                    }
+                    i += 1;
+                }
+                false
+            },
         }
     }
 
@@ -182,17 +508,52 @@ impl AvKeyParameter {
                     .find(|(_, k)| **k == key)
                     .map(|(i, _)| i + 1)
             },
+            AvKeyParameter::LetterKey => {
+                LETTER_KEYS
+                    .iter().enumerate()
+                    .find(|(_, k)| **k == key)
+                    .map(|(i, _)| i)
+            },
+            AvKeyParameter::PrintableKey => {
+                ascii_char(key).map(|c| c as usize)
+            },
+            AvKeyParameter::Custom(p) => {
+                p.keys().iter().position(|k| *k == key)
+            },
         }
     }
-}
 
-impl From<AvKeyParameter> for String {
-    fn from(p: AvKeyParameter) -> Self {
-        match p {
+    ///
+    /// Short code, as written in keybinds without the braces (`d`).
+    /// 
+    const fn short_code(&self) -> &'static str {
+        match self {
             AvKeyParameter::DigitKey => "d",
             AvKeyParameter::FunctionKey => "f",
+            AvKeyParameter::LetterKey => "a",
+            AvKeyParameter::PrintableKey => "printable",
+            AvKeyParameter::Custom(p) => p.code(),
+        }
+    }
+
+    ///
+    /// Position in the canonical order of key parameters:
+    /// built-in ones in declaration order, then custom ones.
+    /// 
+    const fn rank(&self) -> KeyCode {
+        match self {
+            AvKeyParameter::DigitKey => 0,
+            AvKeyParameter::FunctionKey => 1,
+            AvKeyParameter::LetterKey => 2,
+            AvKeyParameter::PrintableKey => 3,
+            AvKeyParameter::Custom(_) => 4,
         }
-        .to_string()
+    }
+}
+
+impl From<AvKeyParameter> for String {
+    fn from(p: AvKeyParameter) -> Self {
+        p.short_code().to_string()
     }
 }
 
@@ -203,6 +564,8 @@ impl TryFrom<String> for AvKeyParameter {
         match value.as_str() {
             "d" => Ok(AvKeyParameter::DigitKey),
             "f" => Ok(AvKeyParameter::FunctionKey),
+            "a" => Ok(AvKeyParameter::LetterKey),
+            "printable" => Ok(AvKeyParameter::PrintableKey),
             _   => Err(value)
         }
     }
@@ -214,28 +577,1950 @@ impl<'a> TryFrom<&'a str> for AvKeyParameter {
         match value {
             "d" => Ok(AvKeyParameter::DigitKey),
             "f" => Ok(AvKeyParameter::FunctionKey),
+            "a" => Ok(AvKeyParameter::LetterKey),
+            "printable" => Ok(AvKeyParameter::PrintableKey),
             _   => Err(value.to_string())
         }
     }
 }
 
-impl ToString for AvKeyParameter {
-    fn to_string(&self) -> String {
-        <Self as Into<String>>::into(self.clone())
+///
+/// Formats as written in a keybind, with braces: `{d}`.
+/// 
+/// For the bare short code (`d`), use `String::from`.
+/// 
+impl std::fmt::Display for AvKeyParameter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{{{}}}", String::from(*self))
     }
 }
 
 
 
-impl PartialEq for AvKey {
-    fn eq(&self, other: &Self) -> bool {
+impl AvKey {
+    ///
+    /// Like `==`, but a fixed key also matches a key parameter covering it:
+    /// `Key(6)` (`5`) matches `Parameter(DigitKey)`, either way round.
+    /// 
+    /// Likewise, either side of a modifier matches an [either side](AvKey::EitherSide)
+    /// one: `Key(97)` (`RightCtrl`) matches `EitherSide(29)` (`Ctrl`).
+    /// 
+    pub fn matches(&self, other : &Self) -> bool {
         match (self, other) {
-            (Self::Key(l), Self::Key(r)) => l == r,
-            (Self::Parameter(_), Self::Parameter(_)) => unimplemented!(),
-            (Self::Key(ref l), Self::Parameter(r)) => r.keys().contains(l),
-            (Self::Parameter(l), Self::Key(ref r)) => l.keys().contains(r)
+            (Self::Key(l), Self::Parameter(r)) => r.matches_code(*l),
+            (Self::Parameter(l), Self::Key(r)) => l.matches_code(*r),
+            (Self::Key(l), Self::EitherSide(r)) | (Self::EitherSide(r), Self::Key(l)) => {
+                *l == *r || other_side(*r) == Some(*l)
+            },
+            _ => self == other,
+        }
+    }
+
+    ///
+    /// Whether two keys can stand for the same physical key, by keycode:
+    /// [either side](AvKey::EitherSide) and [not held](AvKey::NotHeld)
+    /// modifiers cover both sides (`Ctrl` overlaps `RightCtrl` and `!LeftCtrl`).
+    /// Key parameters never overlap.
+    /// 
+    /// Used by `#[AvKeybind]` to reject keys written twice under different names
+    /// (`Esc+Escape`), or both pressed and not held (`Ctrl+A+!Ctrl`).
+    /// 
+    pub const fn overlaps(&self, other : &Self) -> bool {
+        const fn codes(key : &AvKey) -> [Option<KeyCode>; 2] {
+            match key {
+                AvKey::Key(code) => [Some(*code), None],
+                AvKey::EitherSide(code) | AvKey::NotHeld(code) => [Some(*code), other_side(*code)],
+                AvKey::Parameter(_) => [None, None],
+            }
+        }
+
+        let (l, r) = (codes(self), codes(other));
+        let mut i = 0;
+        while i < l.len() {
+            let mut j = 0;
+            while j < r.len() {
+                if let (Some(a), Some(b)) = (l[i], r[j]) {
+                    if a == b {
+                        return true;
+                    }
+                }
+                j += 1;
+            }
+            i += 1;
+        }
+        false
+    }
+}
+
+///
+/// Matches a set of currently pressed keys against a key combination.
+/// 
+/// Every pressed key must be accounted for by exactly one key in the combination:
+/// fixed keys are matched first, then each key parameter (in declaration order)
+/// captures one of the remaining pressed keys. None of the [not held](AvKey::NotHeld)
+/// keys may be pressed.
+/// 
+/// Returns the captured key parameter values (see [AvKeyParameter::value]),
+/// in the same order as the parameters appear in `keys`, or `None` if
+/// the combination does not match.
+/// 
+/// ### Example
+/// ```ignore
+/// let keys = [AvKey::Key(125), AvKey::Parameter(AvKeyParameter::DigitKey)];
+/// 
+/// assert_eq!(matches(&keys, &[125, 4]), Some(vec![3]));   // Logo+3
+/// assert_eq!(matches(&keys, &[125, 30]), None);           // Logo+A
+/// ```
+/// 
+pub fn matches(keys : &[AvKey], pressed : &[KeyCode]) -> Option<Vec<usize>> {
+    if pressed.iter().any(|p| forbids(keys, *p)) {
+        return None;
+    }
+
+    if keys.iter().filter(|k| k.not_held().is_none()).count() != pressed.len() {
+        return None;
+    }
+
+    let mut remaining = pressed.to_vec();
+    take_fixed(keys, &mut remaining)?;
+
+    keys.iter()
+        .filter_map(AvKey::key_parameter)
+        .map(|param| {
+            let i = remaining.iter().position(|p| param.contains(*p))?;
+            param.value(remaining.swap_remove(i))
+        })
+        .collect()
+}
+
+///
+/// Removes the keys held for a combination's fixed keys from `remaining`:
+/// exact keys first, then [either side](AvKey::EitherSide) modifiers.
+/// 
+/// Returns `None` if one of them isn't held.
+/// 
+pub(crate) fn take_fixed(keys : &[AvKey], remaining : &mut Vec<KeyCode>) -> Option<()> {
+    let exact = keys.iter().filter(|k| matches!(k, AvKey::Key(_)));
+    let either = keys.iter().filter(|k| matches!(k, AvKey::EitherSide(_)));
+
+    for key in exact.chain(either) {
+        let i = remaining.iter().position(|p| AvKey::Key(*p).matches(key))?;
+        remaining.swap_remove(i);
+    }
+
+    Some(())
+}
+
+///
+/// Like [matches], but tolerating held modifiers which the combination
+/// does not mention: `Ctrl+A` matches `Ctrl+Shift+A`.
+/// 
+/// Modifiers which must [not be held](AvKey::NotHeld) still may not be.
+/// 
+pub fn matches_loose(keys : &[AvKey], pressed : &[KeyCode]) -> Option<Vec<usize>> {
+    let required = modifier_mask(keys);
+    let either = either_side_mask(keys);
+    let pressed = pressed.iter()
+        .copied()
+        .filter(|p| fold_sides(modifier_bit(*p), either) & !required == 0 || forbids(keys, *p))
+        .collect::<Vec<_>>();
+
+    matches(keys, &pressed)
+}
+
+///
+/// Matches a key press against a key combination, for event-driven dispatch:
+/// the combination fires when its *trigger* key is pressed while the rest of
+/// it is already held.
+/// 
+/// The trigger is the last non-modifier key in `keys` (or the last key,
+/// if they are all modifiers). `held_modifiers` are the keys held before
+/// `just_pressed`, and must complete the combination exactly, as in [matches].
+/// 
+/// ### Example
+/// ```ignore
+/// let keys = parse_keybind("Ctrl+Alt+A")?;
+/// 
+/// assert_eq!(matches_on_press(&keys, &[29, 56], 30), Some(vec![]));  // Hold Ctrl+Alt, press A
+/// assert_eq!(matches_on_press(&keys, &[29, 30], 56), None);          // Hold Ctrl+A, press Alt
+/// ```
+/// 
+pub fn matches_on_press(keys : &[AvKey], held_modifiers : &[KeyCode], just_pressed : KeyCode) -> Option<Vec<usize>> {
+    let mut held = keys.iter().filter(|k| k.not_held().is_none());
+    let trigger = held.clone()
+        .rfind(|k| !k.key().is_some_and(is_modifier))
+        .or_else(|| held.next_back())?;
+
+    let triggered = match trigger {
+        AvKey::Key(k) => *k == just_pressed,
+        AvKey::EitherSide(_) => AvKey::Key(just_pressed).matches(trigger),
+        AvKey::Parameter(p) => p.contains(just_pressed),
+        AvKey::NotHeld(_) => false,
+    };
+
+    if !triggered {
+        return None;
+    }
+
+    let pressed = held_modifiers.iter()
+        .copied()
+        .chain(std::iter::once(just_pressed))
+        .collect::<Vec<_>>();
+
+    matches(keys, &pressed)
+}
+
+///
+/// Broad group of a key, for listing keys by group (see `Key::in_category`).
+/// 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyCategory {
+    ///
+    /// Ctrl, Shift, Alt, or Meta (either side).
+    /// 
+    Modifier,
+
+    ///
+    /// Digit keys, `0` to `9` (not keypad keys).
+    /// 
+    Digit,
+
+    ///
+    /// Function keys, `F1` to `F12`.
+    /// 
+    Function,
+
+    ///
+    /// Arrow keys, `Home`, `End`, `PageUp`, and `PageDown`.
+    /// 
+    Navigation,
+
+    ///
+    /// Letter keys, `A` to `Z`.
+    /// 
+    Letter,
+
+    ///
+    /// Mouse buttons (`BTN_LEFT` to `BTN_TASK`).
+    /// 
+    Mouse,
+
+    ///
+    /// Anything else.
+    /// 
+    Other,
+}
+
+impl KeyCategory {
+    ///
+    /// Returns the category of a keycode.
+    /// 
+    pub const fn of(key : KeyCode) -> Self {
+        match key {
+            _ if is_modifier(key) => KeyCategory::Modifier,
+            2..=11 => KeyCategory::Digit,
+            59..=68 | 87 | 88 => KeyCategory::Function,
+            102..=109 => KeyCategory::Navigation,
+            16..=25 | 30..=38 | 44..=50 => KeyCategory::Letter,
+            0x110..=0x117 => KeyCategory::Mouse,
+            _ => KeyCategory::Other,
         }
     }
 }
 
-impl Eq for AvKey {}
\ No newline at end of file
+///
+/// Physical row of a key in the main (alphanumeric) block
+/// of the keyboard, top to bottom (see `Key::row`).
+/// 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum KeyRow {
+    ///
+    /// `Esc` and `F1` to `F12`.
+    /// 
+    Function,
+
+    ///
+    /// `` ` ``, the digits, `-`, `=`, and `Backspace`.
+    /// 
+    Number,
+
+    ///
+    /// `Tab`, `Q` to `P`, `[`, `]`, and `\`.
+    /// 
+    Top,
+
+    ///
+    /// `CapsLock`, `A` to `L`, `;`, `'`, and `Enter`.
+    /// 
+    Home,
+
+    ///
+    /// The shift keys, and `Z` to `/` between them.
+    /// 
+    Bottom,
+
+    ///
+    /// `Space`, and the modifiers around it.
+    /// 
+    Space,
+}
+
+impl KeyRow {
+    ///
+    /// Returns the row of a keycode, or `None` for keys
+    /// outside the main block (navigation, keypad, media).
+    /// 
+    /// JIS keys are placed in their usual rows.
+    /// 
+    pub const fn of(key : KeyCode) -> Option<Self> {
+        Some(match key {
+            1 | 59..=68 | 87 | 88 => KeyRow::Function,
+            2..=14 | 41 | 85 | 124 => KeyRow::Number,
+            15..=27 | 43 => KeyRow::Top,
+            28 | 30..=40 | 58 => KeyRow::Home,
+            42 | 44..=54 | 86 | 89 => KeyRow::Bottom,
+            29 | 56 | 57 | 92..=94 | 97 | 100 | 125..=127 => KeyRow::Space,
+            _ => return None,
+        })
+    }
+}
+
+///
+/// Checks a key combination can be pressed on a keyboard
+/// with the `available` keys (e.g. as detected through libinput).
+/// 
+/// Returns the missing keys, in combination order: fixed keys, and every
+/// key a key parameter covers. Keys which must [not be held](AvKey::NotHeld)
+/// need not exist.
+/// 
+/// ### Example
+/// ```ignore
+/// let keys = parse_keybind("Ctrl+Yen")?;
+/// assert_eq!(validate_for_keyboard(&keys, &ansi_keys), Err(vec![124]));
+/// ```
+/// 
+pub fn validate_for_keyboard(keys : &[AvKey], available : &[KeyCode]) -> Result<(), Vec<KeyCode>> {
+    let mut missing = vec![];
+    let codes = keys.iter()
+        .flat_map(|k| match k {
+            AvKey::Key(code) | AvKey::EitherSide(code) => std::slice::from_ref(code),
+            AvKey::Parameter(p) => p.keys(),
+            AvKey::NotHeld(_) => &[],
+        });
+
+    for code in codes {
+        if !available.contains(code) && !missing.contains(code) {
+            missing.push(*code);
+        }
+    }
+
+    match missing.is_empty() {
+        true => Ok(()),
+        false => Err(missing),
+    }
+}
+
+///
+/// Whether a combination has `code` as a [not held](AvKey::NotHeld) key,
+/// on either side for modifiers (`!Shift` rules out RightShift too).
+/// 
+pub(crate) fn forbids(keys : &[AvKey], code : KeyCode) -> bool {
+    keys.iter()
+        .filter_map(AvKey::not_held)
+        .any(|k| k == code || other_side(k) == Some(code))
+}
+
+///
+/// Bit of a modifier key in a modifier mask (`0` for other keys).
+/// 
+const fn modifier_bit(key : KeyCode) -> u8 {
+    let mut i = 0;
+    while i < MODIFIER_KEYS.len() {
+        if MODIFIER_KEYS[i] == key {
+            return 1 << i;
+        }
+        i += 1;
+    }
+    0
+}
+
+///
+/// Returns a bitmask of the modifier keys required by a key combination.
+/// 
+/// Bits follow the order `LeftCtrl`, `LeftShift`, `RightShift`, `LeftAlt`,
+/// `RightCtrl`, `RightAlt`, `LeftMeta`, `RightMeta` (least significant first).
+/// 
+/// Key parameters never capture modifiers, so a combination can only
+/// [match](matches) when this is equal to the [pressed keys' mask](pressed_modifier_mask).
+/// 
+/// ### Example
+/// ```ignore
+/// // Ctrl+Shift+A
+/// let keys = [AvKey::Key(29), AvKey::Key(42), AvKey::Key(30)];
+/// assert_eq!(modifier_mask(&keys), 0b11);
+/// ```
+/// 
+pub fn modifier_mask(keys : &[AvKey]) -> u8 {
+    keys.iter()
+        .filter_map(AvKey::key)
+        .fold(0, |mask, k| mask | modifier_bit(k))
+}
+
+///
+/// Returns a bitmask of the [either side](AvKey::EitherSide) modifiers
+/// of a key combination, by their left-side bits (see [modifier_mask]).
+/// 
+pub(crate) fn either_side_mask(keys : &[AvKey]) -> u8 {
+    keys.iter()
+        .filter_map(|k| match k {
+            AvKey::EitherSide(code) => Some(modifier_bit(*code)),
+            _ => None,
+        })
+        .fold(0, |mask, bit| mask | bit)
+}
+
+///
+/// Moves the right-side bits of a modifier mask onto the left-side ones,
+/// for the modifiers in `either` (see [either_side_mask]).
+/// 
+pub(crate) fn fold_sides(mask : u8, either : u8) -> u8 {
+    MODIFIER_SIDES.iter()
+        .map(|(left, right)| (modifier_bit(*left), modifier_bit(*right)))
+        .filter(|(left, right)| either & left != 0 && mask & right != 0)
+        .fold(mask, |mask, (left, right)| mask & !right | left)
+}
+
+///
+/// Whether the held modifiers (`pressed`, see [pressed_modifier_mask]) suit
+/// a combination needing those in `mask`, its [either side](either_side_mask)
+/// ones in `either`: exactly, or when not `exclusive`, with extra ones held too.
+/// 
+pub(crate) fn modifiers_fit(mask : u8, either : u8, pressed : u8, exclusive : bool) -> bool {
+    let pressed = fold_sides(pressed, either);
+    match exclusive {
+        true => mask == pressed,
+        false => mask & !pressed == 0,
+    }
+}
+
+///
+/// Returns a bitmask of the modifier keys currently pressed.
+/// 
+/// See [modifier_mask].
+/// 
+pub fn pressed_modifier_mask(pressed : &[KeyCode]) -> u8 {
+    pressed.iter()
+        .fold(0, |mask, k| mask | modifier_bit(*k))
+}
+
+///
+/// ### Modifier Flags
+/// 
+/// Set of held modifier keys as a bitmask, as compositors usually track
+/// them (e.g. from libinput/xkb), apart from the other held keys.
+/// 
+/// Bits are laid out as in [modifier_mask]. Sources which don't tell
+/// left from right should set the left bits: these still match the
+/// side-less `Ctrl`, `Shift`, `Alt`, and `Meta` key names (see [AvKey::EitherSide]).
+/// 
+/// ### Example
+/// ```ignore
+/// let mods = ModifierFlags::LEFT_CTRL | ModifierFlags::LEFT_SHIFT;
+/// assert_eq!(Vec::<KeyCode>::from(mods), vec![29, 42]);
+/// assert_eq!(ModifierFlags::from(29), ModifierFlags::LEFT_CTRL);
+/// ```
+/// 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ModifierFlags(u8);
+
+impl ModifierFlags {
+    pub const LEFT_CTRL   : Self = Self(1 << 0);
+    pub const LEFT_SHIFT  : Self = Self(1 << 1);
+    pub const RIGHT_SHIFT : Self = Self(1 << 2);
+    pub const LEFT_ALT    : Self = Self(1 << 3);
+    pub const RIGHT_CTRL  : Self = Self(1 << 4);
+    pub const RIGHT_ALT   : Self = Self(1 << 5);
+    pub const LEFT_META   : Self = Self(1 << 6);
+    pub const RIGHT_META  : Self = Self(1 << 7);
+
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    pub const fn from_bits(bits : u8) -> Self {
+        Self(bits)
+    }
+
+    pub const fn bits(&self) -> u8 {
+        self.0
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn contains(&self, other : Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl std::ops::BitOr for ModifierFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs : Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl std::ops::BitOrAssign for ModifierFlags {
+    fn bitor_assign(&mut self, rhs : Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl std::ops::BitAnd for ModifierFlags {
+    type Output = Self;
+
+    fn bitand(self, rhs : Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+///
+/// The flag of a modifier key (empty for other keys).
+/// 
+impl From<KeyCode> for ModifierFlags {
+    fn from(key : KeyCode) -> Self {
+        Self(modifier_bit(key))
+    }
+}
+
+impl FromIterator<KeyCode> for ModifierFlags {
+    fn from_iter<I : IntoIterator<Item = KeyCode>>(iter : I) -> Self {
+        iter.into_iter().fold(Self::empty(), |flags, k| flags | Self::from(k))
+    }
+}
+
+///
+/// The keycodes of the set modifiers, in bit order.
+/// 
+impl From<ModifierFlags> for Vec<KeyCode> {
+    fn from(flags : ModifierFlags) -> Self {
+        MODIFIER_KEYS.iter()
+            .enumerate()
+            .filter(|(i, _)| flags.0 & (1 << i) != 0)
+            .map(|(_, k)| *k)
+            .collect()
+    }
+}
+
+///
+/// Like [matches], for input state split into the held modifiers
+/// (as [flags](ModifierFlags)) and the other held keys.
+/// 
+/// The combination's modifiers must be exactly `mods`, and its other
+/// keys exactly `pressed_non_mods`. Returns the key parameters' values.
+/// 
+/// ### Example
+/// ```ignore
+/// let keys = parse_keybind("Ctrl+Shift+A")?;
+/// let mods = ModifierFlags::LEFT_CTRL | ModifierFlags::LEFT_SHIFT;
+/// 
+/// assert_eq!(matches_with_mods(&keys, mods, &[30]), Some(vec![]));
+/// ```
+/// 
+pub fn matches_with_mods(keys : &[AvKey], mods : ModifierFlags, pressed_non_mods : &[KeyCode]) -> Option<Vec<usize>> {
+    let pressed = Vec::from(mods)
+        .into_iter()
+        .chain(pressed_non_mods.iter().copied().filter(|k| !is_modifier(*k)))
+        .collect::<Vec<_>>();
+
+    matches(keys, &pressed)
+}
+
+///
+/// ### Key Combination
+/// 
+/// An owned combination of [AvKey]s, e.g. `Ctrl+Alt+Del`.
+/// 
+/// Can be collected from (or extended with) any iterator of [AvKey]s,
+/// keeping the keys in iteration order &mdash; use [KeyCombo::from_iter_normalized]
+/// (or [KeyCombo::normalized]) to get the canonical order.
+/// 
+/// Equality, ordering, and hashing all use the canonical order, so
+/// `Ctrl+Shift+A` and `Shift+Ctrl+A` are the same combination
+/// (and the same `HashMap` key).
+/// 
+/// ### Example
+/// ```ignore
+/// let combo = KeyCombo::from_iter_normalized(pressed.iter().copied().map(AvKey::Key));
+/// ```
+/// 
+#[derive(Debug, Clone, Default)]
+pub struct KeyCombo(Vec<AvKey>);
+
+impl KeyCombo {
+    pub fn new(keys : Vec<AvKey>) -> Self {
+        Self(keys)
+    }
+
+    ///
+    /// Collects keys into a combination in its canonical order
+    /// (see [KeyCombo::normalize]), unlike `collect`, which keeps their order.
+    /// 
+    pub fn from_iter_normalized(iter : impl IntoIterator<Item = AvKey>) -> Self {
+        iter.into_iter().collect::<Self>().normalized()
+    }
+
+    ///
+    /// Returns the keys in this combination.
+    /// 
+    pub fn keys(&self) -> &[AvKey] {
+        &self.0
+    }
+
+    ///
+    /// Sorts this combination into its canonical order:
+    /// modifiers first (ascending by keycode), then other fixed keys
+    /// (ascending by keycode), then key parameters, then keys which
+    /// must not be held (ascending by keycode).
+    /// 
+    /// Key parameters keep their relative order,
+    /// since callbacks receive their values by position.
+    /// 
+    pub fn normalize(&mut self) {
+        self.0.sort_by_key(Self::rank);
+    }
+
+    ///
+    /// Sort key of a key in the canonical order. Either side modifiers
+    /// (`Ctrl`) sort right after their left-side key (`LeftCtrl`),
+    /// so the order never depends on the order keys were written in.
+    /// 
+    fn rank(key : &AvKey) -> (u8, KeyCode, bool) {
+        match key {
+            AvKey::Key(code) if is_modifier(*code) => (0, *code, false),
+            AvKey::EitherSide(code) if is_modifier(*code) => (0, *code, true),
+            AvKey::Key(code) => (1, *code, false),
+            AvKey::EitherSide(code) => (1, *code, true),
+            AvKey::Parameter(_) => (2, 0, false),
+            AvKey::NotHeld(code) => (3, *code, false),
+        }
+    }
+
+    ///
+    /// The canonical order of this combination, as comparable values.
+    /// 
+    fn canonical(&self) -> Vec<(u8, KeyCode, bool, &'static str)> {
+        self.clone()
+            .normalized()
+            .0
+            .iter()
+            .map(|k| match k {
+                AvKey::Parameter(p) => (2, p.rank(), false, p.short_code()),
+                k => {
+                    let (rank, code, either) = Self::rank(k);
+                    (rank, code, either, "")
+                },
+            })
+            .collect()
+    }
+
+    ///
+    /// Iterates over the modifier keys of this combination,
+    /// ascending by keycode.
+    /// 
+    pub fn modifiers(&self) -> impl Iterator<Item = &AvKey> {
+        let mut keys = self.0.iter()
+            .filter(|k| k.key().is_some_and(is_modifier))
+            .collect::<Vec<_>>();
+        keys.sort_by_key(|k| Self::rank(k));
+        keys.into_iter()
+    }
+
+    ///
+    /// Iterates over everything but the modifier keys of this combination,
+    /// in canonical order (see [KeyCombo::normalize]).
+    /// 
+    pub fn non_modifiers(&self) -> impl Iterator<Item = &AvKey> {
+        let mut keys = self.0.iter()
+            .filter(|k| !k.key().is_some_and(is_modifier))
+            .collect::<Vec<_>>();
+        keys.sort_by_key(|k| Self::rank(k));
+        keys.into_iter()
+    }
+
+    ///
+    /// Returns this combination in its canonical order
+    /// (see [KeyCombo::normalize]).
+    /// 
+    pub fn normalized(mut self) -> Self {
+        self.normalize();
+        self
+    }
+}
+
+///
+/// Every concrete set of keys a combination stands for: the fixed keys,
+/// with each key parameter filled in with each key it covers, e.g. ten sets
+/// for `Ctrl+{d}`. Keys which must not be held are left out.
+/// 
+/// Useful for registering each combination with grab APIs (X11, Wayland)
+/// which only understand fixed keys.
+/// 
+/// ### Example
+/// ```ignore
+/// let keys = parse_keybind("Ctrl+{d}")?;
+/// assert_eq!(expand(&keys)[0], [29, 11]);
+/// ```
+/// 
+pub fn expand(keys : &[AvKey]) -> Vec<Vec<KeyCode>> {
+    let fixed = keys.iter()
+        .filter_map(|k| match k {
+            AvKey::Key(code) => Some(*code),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    // Either side modifiers stand for both sides.
+    let combos = keys.iter()
+        .filter_map(|k| match k {
+            AvKey::EitherSide(code) => Some([Some(*code), other_side(*code)]),
+            _ => None,
+        })
+        .fold(vec![fixed], |combos, sides| combos.iter()
+            .flat_map(|combo| sides.iter()
+                .flatten()
+                .map(|k| combo.iter().copied().chain([*k]).collect()))
+            .collect());
+
+    keys.iter()
+        .filter_map(AvKey::key_parameter)
+        .fold(combos, |combos, param| combos.iter()
+            .flat_map(|combo| param.keys()
+                .iter()
+                .filter(|k| !combo.contains(k))
+                .map(|k| combo.iter().copied().chain([*k]).collect()))
+            .collect())
+}
+
+///
+/// Whether `keys` has exactly the `expected` key parameters, in order:
+/// an override for a keybind must, as its callback takes one argument per parameter.
+/// 
+/// Backs the `accepts_override` generated by `#[AvKeybind]`, which checks
+/// against the keybind's `EXPECTED_PARAMS`.
+/// 
+/// ### Example
+/// ```ignore
+/// let expected = [AvKeyParameter::DigitKey];
+/// 
+/// assert!(accepts_parameters(&expected, &parse_keybind("Alt+{d}")?));
+/// assert!(!accepts_parameters(&expected, &parse_keybind("Alt+1")?));
+/// ```
+/// 
+pub fn accepts_parameters(expected : &[AvKeyParameter], keys : &[AvKey]) -> bool {
+    keys.iter()
+        .filter_map(AvKey::key_parameter)
+        .eq(expected.iter().cloned())
+}
+
+///
+/// Whether two combinations have the same keys, in any order
+/// (`Ctrl+Shift+A` and `Shift+Ctrl+A`), for use in `const` contexts.
+/// 
+/// Used by [keybinds!](crate::keybinds) to reject clashing default keys at compile time.
+/// 
+pub const fn same_keys(a : &[AvKey], b : &[AvKey]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    // One-to-one: each key is in both as many times (`A+A` isn't `A+B`).
+    let mut i = 0;
+    while i < a.len() {
+        if count_key(a, &a[i]) != count_key(b, &a[i]) {
+            return false;
+        }
+        i += 1;
+    }
+
+    true
+}
+
+///
+/// How many of `keys` are the [same](same_key) as `key`.
+/// 
+const fn count_key(keys : &[AvKey], key : &AvKey) -> usize {
+    let (mut i, mut count) = (0, 0);
+    while i < keys.len() {
+        if same_key(&keys[i], key) {
+            count += 1;
+        }
+        i += 1;
+    }
+
+    count
+}
+
+///
+/// `const` equality of two keys (see [same_keys]).
+/// 
+const fn same_key(a : &AvKey, b : &AvKey) -> bool {
+    match (a, b) {
+        (AvKey::Key(a), AvKey::Key(b))
+            | (AvKey::EitherSide(a), AvKey::EitherSide(b))
+            | (AvKey::NotHeld(a), AvKey::NotHeld(b)) => *a == *b,
+        (AvKey::Parameter(a), AvKey::Parameter(b)) => {
+            let (a, b) = (a.short_code().as_bytes(), b.short_code().as_bytes());
+            if a.len() != b.len() {
+                return false;
+            }
+
+            let mut i = 0;
+            while i < a.len() && a[i] == b[i] {
+                i += 1;
+            }
+            i == a.len()
+        },
+        _ => false,
+    }
+}
+
+///
+/// Turns a snapshot of the held keys (e.g. captured while the user
+/// rebinds a keybind) into a storable combination, in canonical order
+/// (see [KeyCombo::normalize]).
+/// 
+/// ### Example
+/// ```ignore
+/// let keys = combo_from_pressed(&[30, 42, 29]);
+/// assert_eq!(to_macro_syntax(&keys), "LeftCtrl+LeftShift+A");
+/// ```
+/// 
+pub fn combo_from_pressed(pressed : &[KeyCode]) -> Vec<AvKey> {
+    let mut keys = pressed.iter()
+        .copied()
+        .map(AvKey::Key)
+        .collect::<KeyCombo>()
+        .normalized()
+        .0;
+    keys.dedup();
+    keys
+}
+
+///
+/// Plain text form of a key combination, e.g. `Ctrl+Alt+Delete` (with `sep` as `+`),
+/// for tooltips and menus.
+/// 
+/// Keys are named as by [AvKey::to_name_string] (`{d}`, `!Shift`),
+/// and keycodes without a name in brackets (`[199]`). For colored
+/// output in a terminal, see [ColoredCombo].
+/// 
+/// ### Example
+/// ```ignore
+/// let keys = parse_keybind("Ctrl+Alt+Delete")?;
+/// assert_eq!(format_combo(&keys, " + "), "Ctrl + Alt + Delete");
+/// ```
+/// 
+pub fn format_combo(keys : &[AvKey], sep : &str) -> String {
+    keys.iter()
+        .map(|k| k.to_name_string().unwrap_or_else(|UnknownKey(code)| match k {
+            AvKey::NotHeld(_) => format!("![{code}]"),
+            _ => format!("[{code}]"),
+        }))
+        .collect::<Vec<_>>()
+        .join(sep)
+}
+
+///
+/// Displays a key combination `+`-joined, with each key
+/// as its (colored) [Display](AvKey#impl-Display-for-AvKey).
+/// 
+/// ### Example
+/// ```ignore
+/// println!("Press {}", ColoredCombo(&keys));
+/// ```
+/// 
+#[derive(Debug, Clone, Copy)]
+pub struct ColoredCombo<'a>(pub &'a [AvKey]);
+
+impl std::fmt::Display for ColoredCombo<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, key) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, "+")?;
+            }
+            write!(f, "{key}")?;
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq for KeyCombo {
+    fn eq(&self, other: &Self) -> bool {
+        self.canonical() == other.canonical()
+    }
+}
+
+impl Eq for KeyCombo {}
+
+impl std::hash::Hash for KeyCombo {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.canonical().hash(state)
+    }
+}
+
+impl PartialOrd for KeyCombo {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for KeyCombo {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.canonical().cmp(&other.canonical())
+    }
+}
+
+impl From<Vec<AvKey>> for KeyCombo {
+    fn from(keys: Vec<AvKey>) -> Self {
+        Self(keys)
+    }
+}
+
+impl<const N : usize> From<[AvKey; N]> for KeyCombo {
+    fn from(keys: [AvKey; N]) -> Self {
+        Self(keys.to_vec())
+    }
+}
+
+impl From<KeyCombo> for Vec<AvKey> {
+    fn from(combo: KeyCombo) -> Self {
+        combo.0
+    }
+}
+
+impl FromIterator<AvKey> for KeyCombo {
+    fn from_iter<T: IntoIterator<Item = AvKey>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl Extend<AvKey> for KeyCombo {
+    fn extend<T: IntoIterator<Item = AvKey>>(&mut self, iter: T) {
+        self.0.extend(iter)
+    }
+}
+
+///
+/// Error from parsing a keybind string at runtime (see [parse_keybind]).
+/// 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    ///
+    /// Byte offset of the offending token in the parsed string
+    /// (for [parse_key_names], the index of the offending name).
+    /// 
+    pub offset : usize,
+    pub token  : String,
+    pub kind   : ParseErrorKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    ///
+    /// Nothing between two `+`s (or at either end).
+    /// 
+    EmptyKey,
+    ///
+    /// Not a known key name or alias.
+    /// 
+    UnknownKey,
+    ///
+    /// Not an integer inside `[...]`.
+    /// 
+    InvalidKeyCode,
+    ///
+    /// Not a known key parameter inside `{...}`.
+    /// 
+    UnknownParameter,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let token = &self.token;
+        match self.kind {
+            ParseErrorKind::EmptyKey => write!(f, "Expected a key at byte {}", self.offset),
+            ParseErrorKind::UnknownKey => write!(f, "Unknown key `{token}` at byte {}", self.offset),
+            ParseErrorKind::InvalidKeyCode => write!(f, "Invalid key code `{token}` at byte {}", self.offset),
+            ParseErrorKind::UnknownParameter => write!(f, "Unknown key parameter `{token}` at byte {}", self.offset),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+///
+/// Parses a single key, in the syntax of [parse_keybind]
+/// (e.g. `"Ctrl"`, `"[111]"`, `"{d}"`, `"!Shift"`).
+/// 
+impl std::str::FromStr for AvKey {
+    type Err = ParseError;
+
+    fn from_str(s : &str) -> Result<Self, Self::Err> {
+        let token = s.trim_start();
+        parse_key(s.len() - token.len(), token.trim_end())
+    }
+}
+
+///
+/// Parses a combination with [parse_keybind].
+/// 
+impl std::str::FromStr for KeyCombo {
+    type Err = ParseError;
+
+    fn from_str(s : &str) -> Result<Self, Self::Err> {
+        parse_keybind(s).map(KeyCombo)
+    }
+}
+
+///
+/// Parses a keybind string at runtime, using the same `+`-separated
+/// syntax as the [#\[AvKeybind\]](avkeys_macros::AvKeybind) macro:
+/// 
+/// * Key names and aliases: `Ctrl`, `A`, `1` (side-less modifier names
+///   are [either side](AvKey::EitherSide) of the keyboard, as is `!Shift`)
+/// * Escaped characters, as typed on US QWERTY: `'+'`, `'\\'`
+/// * Key codes: `[111]`, `[0x6f]`
+/// * Key parameters: `{d}`, `{f}`
+/// * Keys which must not be held: `!Shift`
+/// 
+/// Whitespace around keys is ignored.
+/// 
+/// ### Example
+/// ```ignore
+/// let keys = parse_keybind("Ctrl+Alt+[111]")?;
+/// assert_eq!(keys, vec![AvKey::EitherSide(29), AvKey::EitherSide(56), AvKey::Key(111)]);
+/// ```
+/// 
+pub fn parse_keybind(s : &str) -> Result<Vec<AvKey>, ParseError> {
+    split_keys(s)
+        .into_iter()
+        .map(|(offset, token)| parse_key(offset, token))
+        .collect()
+}
+
+///
+/// Extra key names, layered over the built-in names and aliases
+/// by [parse_keybind_with]: e.g. a distribution's `Cmd` for `Meta`.
+/// 
+/// ### Example
+/// ```ignore
+/// let overlay = AliasOverlay::new().with("Cmd", Key::LeftMeta);
+/// let keys = parse_keybind_with("Cmd+Space", &overlay)?;
+/// assert_eq!(keys, vec![AvKey::Key(125), AvKey::Key(57)]);
+/// ```
+/// 
+#[derive(Debug, Clone, Default)]
+pub struct AliasOverlay {
+    aliases : HashMap<String, KeyCode>,
+}
+
+impl AliasOverlay {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Adds an alias, replacing any previous one with the same name.
+    /// 
+    /// Aliases take precedence over the built-in names, so can shadow them.
+    /// 
+    pub fn insert(&mut self, name : impl Into<String>, key : impl Into<KeyCode>) {
+        self.aliases.insert(name.into(), key.into());
+    }
+
+    ///
+    /// Builder form of [AliasOverlay::insert].
+    /// 
+    pub fn with(mut self, name : impl Into<String>, key : impl Into<KeyCode>) -> Self {
+        self.insert(name, key);
+        self
+    }
+
+    ///
+    /// Returns the key an alias names, if it is in this overlay.
+    /// 
+    pub fn get(&self, name : &str) -> Option<KeyCode> {
+        self.aliases.get(name).copied()
+    }
+}
+
+///
+/// Like [parse_keybind], also accepting the names in `overlay`
+/// (looked up before the built-in ones).
+/// 
+pub fn parse_keybind_with(s : &str, overlay : &AliasOverlay) -> Result<Vec<AvKey>, ParseError> {
+    split_keys(s)
+        .into_iter()
+        .map(|(offset, token)| parse_key_with(offset, token, Some(overlay)))
+        .collect()
+}
+
+///
+/// Like [parse_keybind], with the key names of a [Keymap]
+/// (e.g. `Ctrl+A` on [AZERTY](Keymap::azerty) is code `16`).
+/// 
+pub fn parse_keybind_in(s : &str, keymap : &Keymap) -> Result<Vec<AvKey>, ParseError> {
+    parse_keybind_with(s, keymap.aliases())
+}
+
+///
+/// Like [parse_keybind], except quoted characters (`'a'`, `'/'`) are the key
+/// typing them on `layout`; each key comes with whether it was given by character.
+/// 
+pub(crate) fn parse_logical_keybind(s : &str, layout : Layout) -> Result<Vec<(AvKey, bool)>, ParseError> {
+    split_keys(s)
+        .into_iter()
+        .map(|(offset, token)| {
+            let ch = token.strip_prefix('\'')
+                .and_then(|t| t.strip_suffix('\''))
+                .map(|ch| ch.strip_prefix('\\').unwrap_or(ch));
+
+            match ch {
+                Some(ch) if ch.chars().count() == 1 => ch.chars()
+                    .next()
+                    .and_then(|c| layout.code_of(c))
+                    .map(|code| (AvKey::Key(code), true))
+                    .ok_or_else(|| ParseError { offset, token : token.to_string(), kind : ParseErrorKind::UnknownKey }),
+                _ => parse_key(offset, token).map(|key| (key, false)),
+            }
+        })
+        .collect()
+}
+
+///
+/// Parses a keybind stored as separate key names, e.g. a JSON array
+/// `["Ctrl", "Alt", "Delete"]`, rather than a `+`-joined string.
+/// 
+/// Each name takes any single-key form accepted by [parse_keybind]
+/// (`Ctrl`, `'+'`, `[111]`, `{d}`, `!Shift`).
+/// 
+/// ### Example
+/// ```ignore
+/// let keys = parse_key_names(&["Ctrl", "Alt", "Delete"])?;
+/// assert_eq!(keys, vec![AvKey::EitherSide(29), AvKey::EitherSide(56), AvKey::Key(111)]);
+/// ```
+/// 
+pub fn parse_key_names(names : &[&str]) -> Result<Vec<AvKey>, ParseError> {
+    names.iter()
+        .enumerate()
+        .map(|(i, name)| parse_key(i, name.trim()))
+        .collect()
+}
+
+///
+/// Parses an Emacs-style keybind string, e.g. `C-M-a`,
+/// for migrating Emacs configs.
+/// 
+/// Modifiers are `-`-terminated prefixes before the final key:
+/// 
+/// | Prefix | Modifier  |
+/// |--------|-----------|
+/// | `C-`   | Ctrl      |
+/// | `M-`   | Alt       |
+/// | `S-`   | Shift     |
+/// | `s-`   | Super     |
+/// 
+/// The final key is a character as typed on US QWERTY (`a`, `-`, `;`),
+/// where uppercase letters imply Shift (`C-A` is `C-S-a`); one of `SPC`,
+/// `RET`, `TAB`, `ESC`, or `DEL`; or a key name in angle brackets (`<f5>`, `<home>`).
+/// Key sequences (`C-x C-c`) are not supported.
+/// 
+/// ### Example
+/// ```ignore
+/// let keys = parse_emacs_keybind("C-M-a")?;
+/// assert_eq!(keys, vec![AvKey::EitherSide(29), AvKey::EitherSide(56), AvKey::Key(30)]);
+/// ```
+/// 
+#[cfg(feature = "emacs-interop")]
+pub fn parse_emacs_keybind(s : &str) -> Result<Vec<AvKey>, ParseError> {
+    let mut keys = vec![];
+    let mut offset = 0;
+
+    loop {
+        let rest = &s[offset..];
+        let modifier = match rest.as_bytes() {
+            [b'C', b'-', _, ..] => 29,
+            [b'M', b'-', _, ..] => 56,
+            [b'S', b'-', _, ..] => 42,
+            [b's', b'-', _, ..] => 125,
+            _ => break,
+        };
+
+        keys.push(AvKey::EitherSide(modifier));
+        offset += 2;
+    }
+
+    let token = &s[offset..];
+    let err = |kind| ParseError { offset, token : token.to_string(), kind };
+
+    let mut chars = token.chars();
+    let key = match (chars.next(), chars.next()) {
+        (None, _) => return Err(err(ParseErrorKind::EmptyKey)),
+        (Some(c), None) => {
+            if c.is_ascii_uppercase() && !keys.contains(&AvKey::EitherSide(42)) {
+                keys.push(AvKey::EitherSide(42));
+            }
+            Layout::UsQwerty.code_of(c)
+        },
+        _ => match token {
+            "SPC" => Some(57),
+            "RET" => Some(28),
+            "TAB" => Some(15),
+            "ESC" => Some(1),
+            "DEL" => Some(14),
+            _ => token.strip_prefix('<')
+                .and_then(|t| t.strip_suffix('>'))
+                .and_then(|name| {
+                    let mut name = name.chars();
+                    let name = name.next()?.to_uppercase().chain(name).collect::<String>();
+                    Key::lookup(name.as_str())
+                })
+                .map(Into::into),
+        },
+    };
+
+    let key = key.ok_or_else(|| err(ParseErrorKind::UnknownKey))?;
+    keys.push(AvKey::Key(key));
+    Ok(keys)
+}
+
+///
+/// Every name a key can be written as: the primary name and aliases
+/// of a fixed (or [not held](AvKey::NotHeld)) key, primary name first,
+/// or the short code of a key parameter (`d`).
+/// 
+/// Empty for codes without a key name. Useful for
+/// "this shortcut can also be written as ..." hints.
+/// 
+pub fn aliases_of(key : &AvKey) -> Vec<&'static str> {
+    match key {
+        AvKey::Key(code) | AvKey::NotHeld(code) => Key::try_from(*code)
+            .map(|k| k.name()
+                .iter()
+                .copied()
+                .filter(|n| either_side_code(n).is_none())
+                .collect())
+            .unwrap_or_default(),
+        AvKey::EitherSide(code) => EITHER_SIDE_NAMES.iter()
+            .filter(|(_, c)| c == code)
+            .map(|(name, _)| *name)
+            .collect(),
+        AvKey::Parameter(p) => vec![p.short_code()],
+    }
+}
+
+///
+/// Formats keys exactly as they would be written inside
+/// [#\[AvKeybind(...)\]](avkeys_macros::AvKeybind), e.g. `LeftCtrl+'='+[199]+{d}`.
+/// 
+/// Keys are written by their primary name, or by their punctuation alias
+/// as a char escape (`'='` rather than `Equal`). Codes without a key name
+/// are written as `[code]`.
+/// 
+/// Unlike the [Display](std::fmt::Display) form, this is always
+/// valid input to the macro and to [parse_keybind].
+/// 
+pub fn to_macro_syntax(keys : &[AvKey]) -> String {
+    let name = |code : KeyCode| match Key::lookup(code) {
+        // `lookup` by code always gives the primary variant.
+        Some(key) => key.name()
+            .iter()
+            .copied()
+            .find(|n| n.len() == 1 && n.as_bytes()[0].is_ascii_punctuation())
+            .map(|n| match n {
+                "\\" | "'" => format!("'\\{n}'"),
+                _ => format!("'{n}'"),
+            })
+            .unwrap_or_else(|| format!("{key:?}")),
+        None => format!("[{code}]"),
+    };
+
+    keys.iter()
+        .map(|k| match k {
+            AvKey::Key(code) => name(*code),
+            AvKey::EitherSide(code) => either_side_name(*code)
+                .map_or_else(|| name(*code), str::to_string),
+            AvKey::Parameter(p) => p.to_string(),
+            AvKey::NotHeld(code) => format!("!{}", name(*code)),
+        })
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+///
+/// Splits a keybind string on `+` (except inside char escapes like `'+'`),
+/// returning each trimmed token with its byte offset.
+/// 
+fn split_keys(s : &str) -> Vec<(usize, &str)> {
+    let mut tokens = vec![];
+    let mut start = 0;
+    let mut in_char = false;
+    let mut escaped = false;
+
+    for (i, c) in s.char_indices() {
+        match c {
+            '\\' if in_char && !escaped => {
+                escaped = true;
+                continue;
+            },
+            '\'' if !escaped => in_char = !in_char,
+            '+' if !in_char => {
+                tokens.push((start, &s[start..i]));
+                start = i + 1;
+            },
+            _ => {}
+        }
+        escaped = false;
+    }
+    tokens.push((start, &s[start..]));
+
+    tokens.into_iter()
+        .map(|(offset, t)| {
+            let trimmed = t.trim_start();
+            (offset + t.len() - trimmed.len(), trimmed.trim_end())
+        })
+        .collect()
+}
+
+///
+/// Parses a single (trimmed) key token.
+/// 
+fn parse_key(offset : usize, token : &str) -> Result<AvKey, ParseError> {
+    parse_key_with(offset, token, None)
+}
+
+///
+/// Like [parse_key], looking names up in `overlay` before the built-in ones.
+/// 
+fn parse_key_with(offset : usize, token : &str, overlay : Option<&AliasOverlay>) -> Result<AvKey, ParseError> {
+    let err = |kind| ParseError { offset, token : token.to_string(), kind };
+
+    if token.is_empty() {
+        return Err(err(ParseErrorKind::EmptyKey));
+    }
+
+    if let Some(negated) = token.strip_prefix('!') {
+        let trimmed = negated.trim_start();
+        let offset = offset + 1 + negated.len() - trimmed.len();
+        return match parse_key_with(offset, trimmed, overlay)? {
+            AvKey::Key(k) | AvKey::EitherSide(k) => Ok(AvKey::NotHeld(k)),
+            _ => Err(err(ParseErrorKind::UnknownKey)),
+        };
+    }
+
+    if let Some(code) = token.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+        let code = code.trim();
+        return match code.strip_prefix("0x") {
+            Some(hex) => KeyCode::from_str_radix(hex, 16),
+            None => code.parse(),
+        }
+        .map(AvKey::Key)
+        .map_err(|_| err(ParseErrorKind::InvalidKeyCode));
+    }
+
+    if let Some(param) = token.strip_prefix('{').and_then(|t| t.strip_suffix('}')) {
+        return AvKeyParameter::try_from(param.trim())
+            .map(AvKey::Parameter)
+            .map_err(|_| err(ParseErrorKind::UnknownParameter));
+    }
+
+    let key = if let Some(ch) = token.strip_prefix('\'').and_then(|t| t.strip_suffix('\'')) {
+        let mut chars = ch.strip_prefix('\\').unwrap_or(ch).chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Key::lookup(c),
+            _ => None,
+        }
+    } else if token.chars().all(|c| c.is_ascii_digit()) {
+        // Bare numbers are digit keys, not keycodes.
+        let mut chars = token.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Key::lookup(c),
+            _ => None,
+        }
+    } else if let Some(code) = overlay.and_then(|o| o.get(token)) {
+        return Ok(AvKey::Key(code));
+    } else if let Some(code) = either_side_code(token) {
+        return Ok(AvKey::EitherSide(code));
+    } else {
+        Key::lookup(token)
+    };
+
+    key.map(|k| AvKey::Key(k.into()))
+        .ok_or_else(|| err(ParseErrorKind::UnknownKey))
+}
+
+///
+/// Serializes as the key's primary name (`"LeftCtrl"`), or its code if it
+/// has none; [either side](AvKey::EitherSide) modifiers by their side-less name (`"Ctrl"`);
+/// parameters as `"{d}"`; and keys which must not be held as `"!LeftShift"`.
+/// 
+#[cfg(feature = "serde")]
+impl serde::Serialize for AvKey {
+    fn serialize<Se : serde::Serializer>(&self, serializer : Se) -> Result<Se::Ok, Se::Error> {
+        match self {
+            AvKey::Key(code) => match Key::lookup(*code) {
+                Some(key) => serializer.serialize_str(&format!("{key:?}")),
+                None => serializer.serialize_u32(*code),
+            },
+            AvKey::EitherSide(code) => match either_side_name(*code) {
+                Some(name) => serializer.serialize_str(name),
+                None => serializer.serialize_u32(*code),
+            },
+            AvKey::Parameter(p) => p.serialize(serializer),
+            AvKey::NotHeld(code) => serializer.serialize_str(&match Key::lookup(*code) {
+                Some(key) => format!("!{key:?}"),
+                None => format!("![{code}]"),
+            }),
+        }
+    }
+}
+
+///
+/// Deserializes from a keycode number, or a single key string
+/// in the syntax of [parse_keybind] (names, `[code]`, `{d}`, `!Shift`).
+/// 
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for AvKey {
+    fn deserialize<D : serde::Deserializer<'de>>(deserializer : D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = AvKey;
+
+            fn expecting(&self, f : &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "a keycode, key name, or key parameter")
+            }
+
+            fn visit_u64<E : serde::de::Error>(self, v : u64) -> Result<AvKey, E> {
+                KeyCode::try_from(v)
+                    .map(AvKey::Key)
+                    .map_err(|_| E::invalid_value(serde::de::Unexpected::Unsigned(v), &self))
+            }
+
+            fn visit_i64<E : serde::de::Error>(self, v : i64) -> Result<AvKey, E> {
+                KeyCode::try_from(v)
+                    .map(AvKey::Key)
+                    .map_err(|_| E::invalid_value(serde::de::Unexpected::Signed(v), &self))
+            }
+
+            fn visit_str<E : serde::de::Error>(self, v : &str) -> Result<AvKey, E> {
+                v.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+///
+/// Serializes as `"{d}"`.
+/// 
+#[cfg(feature = "serde")]
+impl serde::Serialize for AvKeyParameter {
+    fn serialize<Se : serde::Serializer>(&self, serializer : Se) -> Result<Se::Ok, Se::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+///
+/// Deserializes from `"{d}"`, or the bare short code `"d"`.
+/// 
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for AvKeyParameter {
+    fn deserialize<D : serde::Deserializer<'de>>(deserializer : D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        let code = s.strip_prefix('{')
+            .and_then(|s| s.strip_suffix('}'))
+            .unwrap_or(&s);
+
+        AvKeyParameter::try_from(code)
+            .map_err(|_| serde::de::Error::invalid_value(serde::de::Unexpected::Str(&s), &"a key parameter, e.g. `{d}`"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Key;
+
+    use super::{accepts_parameters, aliases_of, ascii_char, combo_from_pressed, expand, format_combo, ColoredCombo, same_keys, matches, matches_loose, matches_on_press, matches_with_mods, modifier_mask, parse_key_names, parse_keybind, parse_keybind_with, to_macro_syntax, validate_for_keyboard, AliasOverlay, AvKey, AvKeyParameter, CustomKeyParameter, KeyCode, KeyCombo, ModifierFlags, ParseErrorKind};
+
+    #[test]
+    fn printable_key_captures_char() {
+        let keys = [Key::LeftMeta.into(), AvKey::Parameter(AvKeyParameter::PrintableKey)];
+        let logo : KeyCode = Key::LeftMeta.into();
+        let a    : KeyCode = Key::A.into();
+        let ctrl : KeyCode = Key::LeftCtrl.into();
+
+        assert_eq!(matches(&keys, &[logo, a]), Some(vec!['a' as usize]));
+        assert_eq!(matches(&keys, &[logo, ctrl]), None);
+    }
+
+    #[test]
+    fn key_combo_from_array() {
+        let combo : KeyCombo = [AvKey::EitherSide(29), AvKey::EitherSide(42), AvKey::Key(30)].into();
+        assert_eq!(combo.keys(), &[AvKey::EitherSide(29), AvKey::EitherSide(42), AvKey::Key(30)]);
+        assert_eq!(combo, "Ctrl+Shift+A".parse().unwrap());
+    }
+
+    #[test]
+    fn key_combo_from_filtered_iter() {
+        let pressed : [KeyCode; 4] = [
+            Key::A.into(), Key::LeftCtrl.into(), Key::Escape.into(), Key::LeftShift.into()
+        ];
+        let escape : KeyCode = Key::Escape.into();
+
+        let mut combo = pressed.into_iter()
+            .filter(|k| *k != escape)
+            .map(AvKey::Key)
+            .collect::<KeyCombo>()
+            .normalized();
+
+        assert_eq!(combo.keys(), &[AvKey::Key(29), AvKey::Key(42), AvKey::Key(30)]);
+
+        combo.extend([AvKey::Parameter(AvKeyParameter::DigitKey)]);
+        assert_eq!(combo.keys().len(), 4);
+
+        let collected = pressed.into_iter().map(AvKey::Key).collect::<KeyCombo>();
+        assert_eq!(collected.keys()[0], AvKey::Key(30));
+
+        let normalized = KeyCombo::from_iter_normalized(pressed.into_iter().map(AvKey::Key));
+        assert_eq!(normalized.keys(), collected.normalized().keys());
+        assert_eq!(normalized.keys()[..2], [AvKey::Key(29), AvKey::Key(42)]);
+    }
+
+    #[test]
+    fn modifier_mask_ctrl_shift_a() {
+        let keys = [Key::LeftCtrl.into(), Key::LeftShift.into(), Key::A.into()];
+
+        assert_eq!(modifier_mask(&keys), 0b0000_0011);
+        assert_eq!(modifier_mask(&[Key::A.into()]), 0);
+    }
+
+    #[test]
+    fn parameter_display_braced() {
+        assert_eq!(AvKeyParameter::DigitKey.to_string(), "{d}");
+        assert_eq!(AvKeyParameter::PrintableKey.to_string(), "{printable}");
+        assert_eq!(String::from(AvKeyParameter::FunctionKey), "f");
+    }
+
+    #[test]
+    fn matches_code_agrees_with_keys() {
+        for param in [AvKeyParameter::DigitKey, AvKeyParameter::FunctionKey, AvKeyParameter::LetterKey, AvKeyParameter::PrintableKey] {
+            assert!((0..256).all(|code| param.matches_code(code) == param.keys().contains(&code)));
+        }
+    }
+
+    #[test]
+    fn expand_parameters() {
+        let keys = [AvKey::Key(29), AvKey::Parameter(AvKeyParameter::DigitKey), AvKey::NotHeld(42)];
+        let combos = expand(&keys);
+        assert_eq!(combos.len(), 10);
+        assert_eq!(combos[0], [29, 11]);
+        assert_eq!(combos[9], [29, 10]);
+
+        let keys = [AvKey::Parameter(AvKeyParameter::DigitKey), AvKey::Parameter(AvKeyParameter::FunctionKey)];
+        assert_eq!(expand(&keys).len(), 120);
+        assert_eq!(expand(&[AvKey::Key(30)]), [[30]]);
+    }
+
+    #[test]
+    fn parameter_order() {
+        let mut params = [AvKeyParameter::FunctionKey, AvKeyParameter::DigitKey];
+        params.sort();
+        assert_eq!(params, [AvKeyParameter::DigitKey, AvKeyParameter::FunctionKey]);
+
+        let (x, y) = (CustomKeyParameter::new("x", &[45]), CustomKeyParameter::new("y", &[21]));
+        let mut params = [AvKeyParameter::Custom(y), AvKeyParameter::PrintableKey, AvKeyParameter::Custom(x)];
+        params.sort();
+        assert_eq!(params, [AvKeyParameter::PrintableKey, AvKeyParameter::Custom(x), AvKeyParameter::Custom(y)]);
+    }
+
+    #[test]
+    fn accepts_parameters_exactly() {
+        let expected = [AvKeyParameter::DigitKey];
+
+        assert!(accepts_parameters(&expected, &parse_keybind("Alt+{d}").unwrap()));
+        assert!(!accepts_parameters(&expected, &parse_keybind("Alt+1").unwrap()));
+        assert!(!accepts_parameters(&expected, &parse_keybind("{a}+{d}").unwrap()));
+        assert!(accepts_parameters(&[], &parse_keybind("Ctrl+A").unwrap()));
+    }
+
+    #[test]
+    fn same_keys_any_order() {
+        const CTRL_SHIFT_A : &[AvKey] = &[AvKey::Key(29), AvKey::Key(42), AvKey::Key(30)];
+        const SHIFT_CTRL_A : &[AvKey] = &[AvKey::Key(42), AvKey::Key(29), AvKey::Key(30)];
+        const _ : () = assert!(same_keys(CTRL_SHIFT_A, SHIFT_CTRL_A));
+
+        assert!(!same_keys(CTRL_SHIFT_A, &CTRL_SHIFT_A[..2]));
+        assert!(!same_keys(&[AvKey::Key(29), AvKey::Key(30)], &[AvKey::Key(29), AvKey::NotHeld(30)]));
+        assert!(same_keys(&[AvKey::Parameter(AvKeyParameter::DigitKey)], &[AvKey::Parameter(AvKeyParameter::DigitKey)]));
+        assert!(!same_keys(&[AvKey::Parameter(AvKeyParameter::DigitKey)], &[AvKey::Parameter(AvKeyParameter::LetterKey)]));
+    }
+
+    #[test]
+    fn same_keys_one_to_one() {
+        let (a, b) = (AvKey::Key(30), AvKey::Key(48));
+        assert!(!same_keys(&[a, a], &[a, b]));
+        assert!(!same_keys(&[a, b], &[a, a]));
+        assert!(same_keys(&[a, b, a], &[a, a, b]));
+
+        let d = AvKey::Parameter(AvKeyParameter::DigitKey);
+        assert!(!same_keys(&[d, d, a], &[d, a, a]));
+    }
+
+    #[test]
+    fn format_combos() {
+        let keys = parse_keybind("Ctrl+Alt+{d}+!Shift+[199]").unwrap();
+        assert_eq!(format_combo(&keys, "+"), "Ctrl+Alt+{d}+!Shift+[199]");
+        assert_eq!(format_combo(&keys[..2], " + "), "Ctrl + Alt");
+        assert_eq!(format_combo(&[], "+"), "");
+
+        assert_eq!(ColoredCombo(&keys[..3]).to_string(), format!("{}+{}+{}", keys[0], keys[1], keys[2]));
+    }
+
+    #[test]
+    fn combo_from_pressed_normalizes() {
+        assert_eq!(
+            combo_from_pressed(&[30, 42, 29, 30]),
+            vec![AvKey::Key(29), AvKey::Key(42), AvKey::Key(30)],
+        );
+        assert!(combo_from_pressed(&[]).is_empty());
+    }
+
+    #[test]
+    fn either_side_modifiers() {
+        let keys = parse_keybind("Ctrl+C").unwrap();
+        assert_eq!(keys, [AvKey::EitherSide(29), AvKey::Key(46)]);
+        assert_eq!(matches(&keys, &[29, 46]), Some(vec![]));
+        assert_eq!(matches(&keys, &[97, 46]), Some(vec![]));
+        assert_eq!(matches(&keys, &[29, 97, 46]), None);
+
+        let left = parse_keybind("LeftCtrl+C").unwrap();
+        assert_eq!(matches(&left, &[97, 46]), None);
+
+        // Exact keys are taken first, so `LeftShift+Shift` needs both shifts.
+        let both = [AvKey::EitherSide(42), AvKey::Key(42), AvKey::Key(30)];
+        assert_eq!(matches(&both, &[42, 54, 30]), Some(vec![]));
+        assert_eq!(matches_loose(&keys, &[97, 42, 46]), Some(vec![]));
+
+        assert_eq!(expand(&keys), [vec![46, 29], vec![46, 97]]);
+        assert_eq!(parse_keybind("!Ctrl").unwrap(), [AvKey::NotHeld(29)]);
+    }
+
+    #[test]
+    fn matches_ctrl_shift_a_with_mods() {
+        let keys = parse_keybind("Ctrl+Shift+A").unwrap();
+        let mods = ModifierFlags::LEFT_CTRL | ModifierFlags::LEFT_SHIFT;
+
+        assert_eq!(matches_with_mods(&keys, mods, &[30]), Some(vec![]));
+        assert_eq!(matches_with_mods(&keys, ModifierFlags::LEFT_CTRL, &[30]), None);
+        assert_eq!(matches_with_mods(&keys, mods | ModifierFlags::LEFT_ALT, &[30]), None);
+
+        assert_eq!([29, 42, 30].into_iter().collect::<ModifierFlags>(), mods);
+        assert_eq!(Vec::<KeyCode>::from(mods), vec![29, 42]);
+    }
+
+    mod custom {
+        crate::keyparameters! {
+            kp  => KeyPadDigit [82, 79, 80, 81, 75, 76, 77, 71, 72, 73],
+            nav => ArrowKey [103, 105, 106, 108],
+        }
+    }
+
+    #[test]
+    fn custom_key_parameters() {
+        use custom::{kp, nav, ArrowKey, KeyPadDigit};
+
+        let keys = [AvKey::Key(29), AvKey::Parameter(kp)];
+        assert_eq!(matches(&keys, &[29, 76]), Some(vec![5]));
+        assert_eq!(matches(&keys, &[29, 6]), None);
+
+        assert!(KeyPadDigit.matches_code(73));
+        assert_eq!(nav.value(108), Some(3));
+        assert_eq!(KeyPadDigit.to_string(), "{kp}");
+        assert_ne!(AvKey::Parameter(KeyPadDigit), AvKey::Parameter(ArrowKey));
+        assert_ne!(
+            KeyCombo::new(vec![AvKey::Parameter(KeyPadDigit)]),
+            KeyCombo::new(vec![AvKey::Parameter(ArrowKey)]),
+        );
+    }
+
+    #[test]
+    fn letter_key_values() {
+        let letters = AvKeyParameter::LetterKey;
+
+        assert_eq!(letters.value(30), Some(0));  // A
+        assert_eq!(letters.value(16), Some(16)); // Q
+        assert_eq!(letters.value(44), Some(25)); // Z
+        assert_eq!(letters.value(2), None);
+        assert!(letters.keys().iter().enumerate().all(|(i, k)| ascii_char(*k) == Some((b'a' + i as u8) as char)));
+        assert_eq!("{a}".parse::<AvKey>(), Ok(AvKey::Parameter(letters)));
+    }
+
+    #[test]
+    #[cfg(feature = "emacs-interop")]
+    fn parse_emacs() {
+        use super::parse_emacs_keybind;
+
+        let codes = |s| parse_emacs_keybind(s).unwrap()
+            .iter()
+            .filter_map(AvKey::key)
+            .collect::<Vec<_>>();
+
+        assert_eq!(codes("C-M-a"), [29, 56, 30]);
+        assert_eq!(codes("C--"), [29, 12]);
+        assert_eq!(codes("C-A"), [29, 42, 30]);
+        assert_eq!(codes("s-<f5>"), [125, 63]);
+        assert_eq!(codes("M-RET"), [56, 28]);
+
+        let err = parse_emacs_keybind("C-M-foo").unwrap_err();
+        assert_eq!((err.offset, err.kind), (4, ParseErrorKind::UnknownKey));
+        assert_eq!(parse_emacs_keybind("C-").unwrap_err().kind, ParseErrorKind::UnknownKey);
+    }
+
+    #[test]
+    fn from_str() {
+        assert_eq!(" Ctrl ".parse::<AvKey>().unwrap().key(), Some(29));
+        assert_eq!("{d}".parse::<AvKey>().unwrap().key_parameter(), Some(AvKeyParameter::DigitKey));
+        assert_eq!("'+'".parse::<AvKey>().unwrap().key(), Some(13));
+
+        let err = "  Nope".parse::<AvKey>().unwrap_err();
+        assert_eq!((err.offset, err.kind), (2, ParseErrorKind::UnknownKey));
+
+        let combo : KeyCombo = "Ctrl+Alt+Del".parse().unwrap();
+        assert_eq!(combo.keys().iter().filter_map(AvKey::key).collect::<Vec<_>>(), [29, 56, 111]);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn avkey_serde_round_trip() {
+        let keys = vec![AvKey::Key(29), AvKey::Parameter(AvKeyParameter::DigitKey), AvKey::Key(199), AvKey::NotHeld(42)];
+        let json = serde_json::to_string(&keys).unwrap();
+        assert_eq!(json, r#"["LeftCtrl","{d}",199,"!LeftShift"]"#);
+
+        let back : Vec<AvKey> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.len(), keys.len());
+        assert!(back.iter().zip(&keys).all(|(b, k)| b.key() == k.key()
+            && b.key_parameter() == k.key_parameter()
+            && b.not_held() == k.not_held()));
+
+        let param : AvKeyParameter = serde_json::from_str(r#""f""#).unwrap();
+        assert_eq!(param, AvKeyParameter::FunctionKey);
+        assert!(serde_json::from_str::<AvKey>(r#""Nope""#).is_err());
+    }
+
+    #[test]
+    fn validate_keyboard() {
+        let available = (1..=88).collect::<Vec<KeyCode>>();
+
+        assert_eq!(validate_for_keyboard(&parse_keybind("Ctrl+Alt+{f}").unwrap(), &available), Ok(()));
+        assert_eq!(validate_for_keyboard(&parse_keybind("Ctrl+Yen+!Muhenkan").unwrap(), &available), Err(vec![124]));
+    }
+
+    #[test]
+    fn key_combo_canonical_eq() {
+        use std::collections::HashMap;
+
+        let ctrl_shift_a : KeyCombo = "Ctrl+Shift+A".parse().unwrap();
+        let shift_a_ctrl : KeyCombo = "A+Shift+Ctrl".parse().unwrap();
+        assert_eq!(ctrl_shift_a, shift_a_ctrl);
+        assert_ne!(ctrl_shift_a, "Ctrl+A".parse().unwrap());
+        assert_ne!("Ctrl+{d}+{f}".parse::<KeyCombo>().unwrap(), "Ctrl+{f}+{d}".parse().unwrap());
+
+        let mods = shift_a_ctrl.modifiers().filter_map(AvKey::key).collect::<Vec<_>>();
+        assert_eq!(mods, [29, 42]);
+        assert_eq!(shift_a_ctrl.non_modifiers().filter_map(AvKey::key).collect::<Vec<_>>(), [30]);
+
+        let map = HashMap::from([(ctrl_shift_a, "SelectAll")]);
+        assert_eq!(map.get(&shift_a_ctrl), Some(&"SelectAll"));
+
+        // Either side `Ctrl` sorts after `LeftCtrl`, whichever comes first.
+        let either_first = KeyCombo::new(vec![AvKey::EitherSide(29), AvKey::Key(29), AvKey::Key(30)]);
+        let left_first = KeyCombo::new(vec![AvKey::Key(29), AvKey::EitherSide(29), AvKey::Key(30)]);
+        assert_eq!(either_first, left_first);
+        assert_eq!(either_first.clone().normalized().keys(), left_first.clone().normalized().keys());
+        assert_eq!(HashMap::from([(either_first, 1)]).get(&left_first), Some(&1));
+    }
+
+    #[test]
+    fn parameter_eq_parameter() {
+        let digit = AvKey::Parameter(AvKeyParameter::DigitKey);
+
+        assert_eq!(digit, AvKey::Parameter(AvKeyParameter::DigitKey));
+        assert_ne!(digit, AvKey::Parameter(AvKeyParameter::FunctionKey));
+
+        let letter = AvKey::Parameter(AvKeyParameter::LetterKey);
+        let mut keys = vec![digit, digit, letter, AvKey::Key(29), digit];
+        keys.dedup_by(|l, r| l == r);
+        assert_eq!(keys, [digit, letter, AvKey::Key(29), digit]);
+    }
+
+    #[test]
+    fn overlaps() {
+        assert!(AvKey::Key(1).overlaps(&AvKey::Key(1)));
+        assert!(AvKey::EitherSide(56).overlaps(&AvKey::Key(100)));
+        assert!(AvKey::Key(29).overlaps(&AvKey::NotHeld(97)));
+        assert!(!AvKey::Key(29).overlaps(&AvKey::Key(97)));
+        assert!(!AvKey::Key(2).overlaps(&AvKey::Parameter(AvKeyParameter::DigitKey)));
+
+        // As used by `#[AvKeybind]`.
+        const _ : () = assert!(AvKey::EitherSide(125).overlaps(&AvKey::EitherSide(125)));
+    }
+
+    #[test]
+    fn exact_eq_and_hash() {
+        let digit = AvKey::Parameter(AvKeyParameter::DigitKey);
+
+        assert_ne!(AvKey::Key(6), digit);
+        assert!(AvKey::Key(6).matches(&digit));
+        assert!(digit.matches(&AvKey::Key(6)));
+        assert!(!AvKey::NotHeld(6).matches(&digit));
+
+        let mut keybinds = std::collections::HashMap::new();
+        keybinds.insert(vec![AvKey::Key(29), digit], "SwitchTab");
+        keybinds.insert(vec![AvKey::Key(29), AvKey::Key(6)], "QuickAction");
+
+        assert_eq!(keybinds.len(), 2);
+        assert_eq!(keybinds[&vec![AvKey::Key(29), AvKey::Key(6)]], "QuickAction");
+    }
+
+    #[test]
+    fn exact_eq_in_collections() {
+        let digit = AvKey::Parameter(AvKeyParameter::DigitKey);
+        let mut keys = vec![AvKey::Key(2), digit, AvKey::Key(3)];
+
+        assert!(!keys.contains(&AvKey::Key(4)));
+        assert!(keys.iter().any(|k| k.matches(&AvKey::Key(4))));
+
+        keys.dedup();
+        assert_eq!(keys.len(), 3);
+    }
+
+    #[test]
+    fn matches_on_trigger_press() {
+        let keys = parse_keybind("Ctrl+Alt+A").unwrap();
+
+        assert_eq!(matches_on_press(&keys, &[29, 56], 30), Some(vec![]));
+        assert_eq!(matches_on_press(&keys, &[29, 30], 56), None);
+        assert_eq!(matches_on_press(&keys, &[29], 30), None);
+
+        let keys = parse_keybind("Logo+{d}").unwrap();
+        assert_eq!(matches_on_press(&keys, &[125], 4), Some(vec![3]));
+    }
+
+    #[test]
+    fn generated_parameter_keys() {
+        assert_eq!(super::DIGIT_KEYS, [11, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+        assert_eq!(super::FUNCTION_KEYS, [59, 60, 61, 62, 63, 64, 65, 66, 67, 68, 87, 88]);
+        assert_eq!(super::LETTER_KEYS, [
+            30, 48, 46, 32, 18, 33, 34, 35, 23, 36, 37, 38, 50,
+            49, 24, 25, 16, 19, 31, 20, 22, 47, 17, 45, 21, 44,
+        ]);
+    }
+
+    #[test]
+    fn aliases_of_keys() {
+        let names = aliases_of(&AvKey::Key(29));
+        assert_eq!(names[0], "LeftCtrl");
+        assert!(!names.contains(&"Ctrl"));
+        assert_eq!(aliases_of(&AvKey::EitherSide(29)), ["Ctrl"]);
+
+        assert_eq!(aliases_of(&AvKey::Key(12)), ["Minus", "-"]);
+        assert_eq!(aliases_of(&AvKey::Parameter(AvKeyParameter::DigitKey)), ["d"]);
+        assert!(aliases_of(&AvKey::Key(199)).is_empty());
+    }
+
+    #[test]
+    fn macro_syntax_round_trip() {
+        let keys = [
+            AvKey::Key(29), AvKey::Key(13), AvKey::Key(199),
+            AvKey::Parameter(AvKeyParameter::DigitKey), AvKey::NotHeld(42),
+        ];
+        let s = to_macro_syntax(&keys);
+        assert_eq!(s, "LeftCtrl+'='+[199]+{d}+!LeftShift");
+
+        let parsed = parse_keybind(&s).unwrap();
+        assert!(parsed.iter().zip(keys.iter()).all(|(p, k)| p.key() == k.key() && p.key_parameter() == k.key_parameter() && p.not_held() == k.not_held()));
+
+        #[cfg(feature = "parsing")]
+        {
+            let parsed : avkeys_common::ParsedKeybind = syn::parse_str(&s).unwrap();
+            assert_eq!(parsed.iter().count(), keys.len());
+            assert!(parsed.validate_key_names().is_none());
+        }
+    }
+
+    #[test]
+    fn parse_keybind_runtime() {
+        let keys = parse_keybind("Ctrl + '+' + [0x6f] + {d} + 1").unwrap();
+        let expected = [
+            AvKey::Key(29), AvKey::Key(13), AvKey::Key(111),
+            AvKey::Parameter(AvKeyParameter::DigitKey), AvKey::Key(2),
+        ];
+
+        assert_eq!(keys.len(), expected.len());
+        assert!(keys.iter().zip(expected.iter()).all(|(k, e)| k.key() == e.key() && k.key_parameter() == e.key_parameter()));
+
+        let err = parse_keybind("Ctrl+Nope").unwrap_err();
+        assert_eq!((err.offset, err.kind), (5, ParseErrorKind::UnknownKey));
+        assert_eq!(parse_keybind("Ctrl++A").unwrap_err().kind, ParseErrorKind::EmptyKey);
+    }
+
+    #[test]
+    fn parse_keybind_alias_overlay() {
+        let overlay = AliasOverlay::new()
+            .with("Cmd", Key::LeftMeta)
+            .with("Hyper", 0x1b4 as KeyCode);
+
+        assert_eq!(parse_keybind_with("Cmd+Shift+!Hyper", &overlay), Ok(vec![AvKey::Key(125), AvKey::EitherSide(42), AvKey::NotHeld(0x1b4)]));
+        assert_eq!(parse_keybind_with("Ctrl+{d}", &overlay), parse_keybind("Ctrl+{d}"));
+        assert_eq!(parse_keybind("Cmd+Space").unwrap_err().kind, ParseErrorKind::UnknownKey);
+
+        // Overlaid names shadow built-in ones.
+        let overlay = overlay.with("Ctrl", Key::CapsLock);
+        assert_eq!(parse_keybind_with("Ctrl+A", &overlay), Ok(vec![AvKey::Key(58), AvKey::Key(30)]));
+    }
+
+    #[test]
+    fn parse_key_names_array() {
+        assert_eq!(
+            parse_key_names(&["Ctrl", "Alt", "Delete"]),
+            Ok(vec![AvKey::EitherSide(29), AvKey::EitherSide(56), AvKey::Key(111)]),
+        );
+
+        let err = parse_key_names(&["Ctrl", " {d} ", "Ctrl+A"]).unwrap_err();
+        assert_eq!((err.offset, err.kind), (2, ParseErrorKind::UnknownKey));
+    }
+
+    #[test]
+    fn not_held_key() {
+        let keys = parse_keybind("Ctrl+A+!Shift").unwrap();
+        let ctrl  : KeyCode = Key::LeftCtrl.into();
+        let shift : KeyCode = Key::LeftShift.into();
+        let a     : KeyCode = Key::A.into();
+
+        let right_shift : KeyCode = Key::RightShift.into();
+
+        assert_eq!(keys[2].not_held(), Some(shift));
+        assert_eq!(matches(&keys, &[ctrl, a]), Some(vec![]));
+        assert_eq!(matches(&keys, &[ctrl, shift, a]), None);
+
+        // Either Shift.
+        assert_eq!(matches(&keys, &[ctrl, right_shift, a]), None);
+        assert_eq!(matches_loose(&keys, &[ctrl, right_shift, a]), None);
+        assert_eq!(matches_loose(&keys, &[ctrl, 56, a]), Some(vec![]));
+    }
+}
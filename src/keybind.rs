@@ -0,0 +1,493 @@
+use std::fmt;
+use std::time::Duration;
+
+use crate::{accepts_parameters, AvKey, AvKeyParameter, KeyCode, KeyCombo, ParseError, TriggerMode};
+
+///
+/// ### Keybind
+/// 
+/// A keyboard shortcut and its callback.
+/// 
+/// Usually implemented through the [#\[AvKeybind\]](avkeys_macros::AvKeybind) macro,
+/// which wraps a callback function into a struct implementing this trait.
+/// 
+/// `S` is the state passed to the callback (e.g. the window manager's),
+/// and `R` what the callback returns (e.g. a command for the compositor
+/// to carry out); both `()` by default.
+/// 
+/// [Registry](crate::Registry) and [Dispatcher](crate::Dispatcher) only
+/// hold keybinds returning `()`.
+/// 
+pub trait AvKeybind<S = (), R = ()> {
+    ///
+    /// The combination this keybind ships with.
+    /// 
+    fn default_keys() -> &'static [AvKey]
+        where Self : Sized;
+
+    ///
+    /// The current combination: the user's override, if any,
+    /// else the [default keys](AvKeybind::default_keys).
+    /// 
+    fn keys(&self) -> &[AvKey];
+
+    ///
+    /// For sequence keybinds (e.g. `Ctrl+K` then `Ctrl+C`), the combinations
+    /// to press one after another, after [keys](AvKeybind::keys).
+    /// 
+    /// Empty for ordinary keybinds.
+    /// 
+    fn then(&self) -> &[KeyCombo] {
+        &[]
+    }
+
+    ///
+    /// The user's override of the [default keys](AvKeybind::default_keys),
+    /// if any.
+    /// 
+    fn keys_override(&mut self) -> &mut Option<Vec<AvKey>>;
+
+    ///
+    /// Whether the current keys differ from the [default keys](AvKeybind::default_keys)
+    /// (an override identical to the defaults doesn't count).
+    /// 
+    fn is_modified(&self) -> bool;
+
+    ///
+    /// Drops the user's override, going back to the [default keys](AvKeybind::default_keys).
+    /// 
+    /// Keybinds held in a [Registry](crate::Registry) should be reset
+    /// through [Registry::set_override](crate::Registry::set_override) instead,
+    /// so it can keep its lookup data up to date.
+    /// 
+    fn reset(&mut self) {
+        *self.keys_override() = None;
+    }
+
+    ///
+    /// Overrides the [default keys](AvKeybind::default_keys), if `keys` have
+    /// the same key parameters (`{d}`, `{f}`, ...) as them, in the same order:
+    /// the callback takes the parameters' values by position, so they can't be
+    /// added, dropped, or reordered.
+    /// 
+    /// As with [reset](AvKeybind::reset), keybinds held in a [Registry](crate::Registry)
+    /// should be overridden through it instead.
+    /// 
+    fn set_keys(&mut self, keys : Vec<AvKey>) -> Result<(), OverrideError> {
+        // The defaults are only reachable through `keys()` without an override.
+        let previous = self.keys_override().take();
+        let expected = self.keys()
+            .iter()
+            .filter_map(AvKey::key_parameter)
+            .collect::<Vec<_>>();
+
+        if !accepts_parameters(&expected, &keys) {
+            *self.keys_override() = previous;
+            return Err(OverrideError::ParameterMismatch {
+                expected,
+                found : keys.iter().filter_map(AvKey::key_parameter).collect(),
+            });
+        }
+
+        *self.keys_override() = Some(keys);
+        Ok(())
+    }
+
+    ///
+    /// Runs the callback, with the values of any
+    /// captured key parameters (in declaration order),
+    /// returning what the callback returns.
+    /// 
+    fn run(&self, state : &mut S, params : Vec<usize>) -> R;
+
+    ///
+    /// Identifier of this keybind (the callback's name, e.g. `AvSearch`),
+    /// which saved overrides are keyed by (see [Registry::load_toml](crate::Registry::load_toml)).
+    /// 
+    /// It is stable as long as the callback isn't renamed, but isn't namespaced:
+    /// keybinds from different crates (or modules) with the same name share an ID,
+    /// and a [Registry](crate::Registry) only ever finds the first one registered.
+    /// Keep names unique across everything registered together, e.g. with
+    /// a crate prefix (`FilesSearch`, `TermSearch`).
+    /// 
+    fn id(&self) -> &'static str;
+
+    ///
+    /// Human-readable description (the callback's rustdoc comment).
+    /// 
+    fn description(&self) -> &'static str;
+
+    ///
+    /// Whether this keybind only fires when exactly its modifiers are held
+    /// (`#[AvKeybind(Ctrl+A, exclusive)]`).
+    /// 
+    /// Otherwise, extra held modifiers are tolerated when no other keybind
+    /// matches exactly: `Ctrl+A` also fires for `Ctrl+Shift+A`.
+    /// 
+    fn exclusive_modifiers(&self) -> bool {
+        false
+    }
+
+    ///
+    /// Whether this keybind fires as its keys are pressed,
+    /// or as they are released (`#[AvKeybind(Logo+V, on = release)]`).
+    /// 
+    fn trigger(&self) -> TriggerMode {
+        TriggerMode::Press
+    }
+
+    ///
+    /// How long the keys must be held before this keybind fires, in milliseconds
+    /// (`#[AvKeybind(Power, hold_ms = 2000)]`), for long-press shortcuts.
+    /// 
+    /// Read through [hold_duration](AvKeybind::hold_duration).
+    /// 
+    fn hold_duration_ms(&self) -> Option<u64> {
+        None
+    }
+
+    ///
+    /// [hold_duration_ms](AvKeybind::hold_duration_ms) as a [Duration]
+    /// (also `#[AvKeybind(Power hold 2s)]`).
+    /// 
+    /// A [Dispatcher](crate::Dispatcher) fires the keybind once its keys were held
+    /// this long (see [Dispatcher#long-presses](crate::Dispatcher#long-presses));
+    /// it only applies to keybinds [triggered](AvKeybind::trigger) on press.
+    /// 
+    fn hold_duration(&self) -> Option<Duration> {
+        self.hold_duration_ms().map(Duration::from_millis)
+    }
+
+    ///
+    /// The modifier anchoring a repeated-chord interaction
+    /// (`#[AvKeybind(Alt+Tab, anchor = Alt)]`): pressing the rest of the chord
+    /// again while it stays held repeats the keybind (e.g. cycling windows),
+    /// and releasing it finishes the interaction (e.g. focusing the selected one).
+    /// 
+    /// The [Dispatcher](crate::Dispatcher#anchors) reports the release
+    /// through [on_anchor_release](crate::Dispatcher::on_anchor_release).
+    /// 
+    fn anchor_modifier(&self) -> Option<KeyCode> {
+        None
+    }
+
+    ///
+    /// Capabilities this keybind needs to run, e.g. `"screenshot"`
+    /// (`#[AvKeybind(PrintScreen, requires = ["screenshot"])]`).
+    /// 
+    /// See [Dispatcher::provide](crate::Dispatcher::provide).
+    /// 
+    fn requires(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    ///
+    /// Whether the current keys have any key parameters (`{d}`, `{f}`, ...).
+    /// 
+    fn has_parameters(&self) -> bool {
+        self.parameter_count() > 0
+    }
+
+    ///
+    /// How many key parameters the current keys have,
+    /// i.e. how many values [run](AvKeybind::run) receives.
+    /// 
+    fn parameter_count(&self) -> usize {
+        self.keys()
+            .iter()
+            .filter_map(AvKey::key_parameter)
+            .count()
+    }
+
+    ///
+    /// Every concrete set of keys the current keys stand for,
+    /// one per way of filling in the key parameters (see [expand](crate::expand)).
+    /// 
+    fn expand(&self) -> Vec<Vec<KeyCode>> {
+        crate::expand(self.keys())
+    }
+
+    ///
+    /// Returns a snapshot of this keybind's metadata.
+    /// 
+    fn info(&self) -> KeybindInfo {
+        KeybindInfo {
+            id          : self.id(),
+            description : self.description(),
+            keys        : self.keys().to_vec(),
+        }
+    }
+}
+
+///
+/// Error from overriding a keybind's keys (see [AvKeybind::set_keys]).
+/// 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OverrideError {
+    ///
+    /// The keys' key parameters (`found`) aren't the default keys' (`expected`),
+    /// in the same order.
+    /// 
+    ParameterMismatch { expected : Vec<AvKeyParameter>, found : Vec<AvKeyParameter> },
+
+    ///
+    /// The keybind string does not parse
+    /// (see [Registry::set_logical_override](crate::Registry::set_logical_override)).
+    /// 
+    InvalidKeys(ParseError),
+}
+
+impl From<ParseError> for OverrideError {
+    fn from(error : ParseError) -> Self {
+        OverrideError::InvalidKeys(error)
+    }
+}
+
+impl fmt::Display for OverrideError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |params : &[AvKeyParameter]| match params.is_empty() {
+            true => "none".to_string(),
+            false => params.iter().map(|p| format!("`{p}`")).collect::<Vec<_>>().join(", "),
+        };
+
+        match self {
+            OverrideError::ParameterMismatch { expected, found } => write!(
+                f, "Expected the key parameters {}, found {}",
+                list(expected), list(found),
+            ),
+            OverrideError::InvalidKeys(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for OverrideError {}
+
+///
+/// Metadata of a keybind, detached from its callback.
+/// 
+/// See [AvKeybind::info].
+/// 
+#[derive(Debug, Clone)]
+pub struct KeybindInfo {
+    pub id          : &'static str,
+    pub description : &'static str,
+    pub keys        : Vec<AvKey>,
+}
+
+///
+/// A keybind submitted to the global collection by
+/// [#\[AvKeybind\]](avkeys_macros::AvKeybind), gathered
+/// by [Registry::from_inventory](crate::Registry::from_inventory).
+/// 
+/// Only keybinds with the default `()` state, returning `()`, are collected.
+/// 
+#[cfg(feature = "inventory")]
+pub struct KeybindRegistration {
+    pub(crate) constructor : fn() -> Box<dyn AvKeybind>,
+}
+
+#[cfg(feature = "inventory")]
+impl KeybindRegistration {
+    pub const fn new(constructor : fn() -> Box<dyn AvKeybind>) -> Self {
+        Self { constructor }
+    }
+}
+
+#[cfg(feature = "inventory")]
+inventory::collect!(KeybindRegistration);
+
+#[cfg(test)]
+pub(crate) mod testing {
+    use std::marker::PhantomData;
+    use std::time::Duration;
+
+    use crate::{AvKey, KeyCombo, TriggerMode};
+
+    use super::AvKeybind;
+
+    ///
+    /// The ID and parameters of each run of a [recording](TestKeybind::recording) keybind.
+    /// 
+    pub(crate) type Record = Vec<(&'static str, Vec<usize>)>;
+
+    ///
+    /// States [TestKeybind] can run with: `()`, or a [Record] of its runs.
+    /// 
+    pub(crate) trait Runs {
+        fn record(&mut self, id : &'static str, params : Vec<usize>);
+    }
+
+    impl Runs for () {
+        fn record(&mut self, _ : &'static str, _ : Vec<usize>) {}
+    }
+
+    impl Runs for Record {
+        fn record(&mut self, id : &'static str, params : Vec<usize>) {
+            self.push((id, params));
+        }
+    }
+
+    ///
+    /// A keybind with a runtime-defined default, for tests.
+    /// 
+    /// `S` is `()`, unless it [records](TestKeybind::recording) its runs.
+    /// 
+    pub(crate) struct TestKeybind<S = ()> {
+        pub id          : &'static str,
+        pub description : &'static str,
+        pub default     : Vec<AvKey>,
+        pub keys        : Option<Vec<AvKey>>,
+        pub exclusive   : bool,
+        pub then        : Vec<KeyCombo>,
+        pub trigger     : TriggerMode,
+        pub requires    : &'static [&'static str],
+        pub hold        : Option<Duration>,
+        pub _state      : PhantomData<fn(&mut S)>,
+    }
+
+    impl TestKeybind {
+        pub fn new(id : &'static str, default : &[AvKey]) -> Self {
+            Self::with_state(id, default)
+        }
+    }
+
+    impl TestKeybind<Record> {
+        ///
+        /// A keybind pushing its ID and parameters onto the [Record] it runs with.
+        /// 
+        pub fn recording(id : &'static str, default : &[AvKey]) -> Self {
+            Self::with_state(id, default)
+        }
+    }
+
+    impl<S> TestKeybind<S> {
+        fn with_state(id : &'static str, default : &[AvKey]) -> Self {
+            Self {
+                id,
+                description : "",
+                default     : default.to_vec(),
+                keys        : None,
+                exclusive   : false,
+                then        : vec![],
+                trigger     : TriggerMode::Press,
+                requires    : &[],
+                hold        : None,
+                _state      : PhantomData,
+            }
+        }
+    }
+
+    impl<S : Runs> AvKeybind<S> for TestKeybind<S> {
+        fn default_keys() -> &'static [AvKey] {
+            &[]
+        }
+
+        fn keys(&self) -> &[AvKey] {
+            self.keys.as_deref().unwrap_or(&self.default)
+        }
+
+        fn keys_override(&mut self) -> &mut Option<Vec<AvKey>> {
+            &mut self.keys
+        }
+
+        fn is_modified(&self) -> bool {
+            self.keys.as_ref().is_some_and(|keys| *keys != self.default)
+        }
+
+        fn run(&self, state : &mut S, params : Vec<usize>) {
+            state.record(self.id, params);
+        }
+
+        fn id(&self) -> &'static str {
+            self.id
+        }
+
+        fn description(&self) -> &'static str {
+            self.description
+        }
+
+        fn exclusive_modifiers(&self) -> bool {
+            self.exclusive
+        }
+
+        fn trigger(&self) -> TriggerMode {
+            self.trigger
+        }
+
+        fn requires(&self) -> &'static [&'static str] {
+            self.requires
+        }
+
+        fn then(&self) -> &[KeyCombo] {
+            &self.then
+        }
+
+        fn hold_duration(&self) -> Option<Duration> {
+            self.hold
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AvKey, AvKeyParameter, AvKeybind};
+
+    use super::testing::TestKeybind;
+    use super::OverrideError;
+
+    #[test]
+    fn override_then_reset() {
+        let mut keybind = TestKeybind::new("AvSearch", &[AvKey::Key(125), AvKey::Key(57)]);
+        assert!(!keybind.is_modified());
+
+        *keybind.keys_override() = Some(vec![AvKey::Key(125), AvKey::Key(57)]);
+        assert!(!keybind.is_modified());
+
+        *keybind.keys_override() = Some(vec![AvKey::Key(29), AvKey::Key(57)]);
+        assert!(keybind.is_modified());
+
+        keybind.reset();
+        assert!(!keybind.is_modified());
+        assert!(keybind.keys_override().is_none());
+        assert_eq!(keybind.keys(), &[AvKey::Key(125), AvKey::Key(57)]);
+    }
+
+    #[test]
+    fn set_keys_checks_parameters() {
+        let mut keybind = TestKeybind::new("SwitchTab", &[AvKey::Key(29), AvKey::Parameter(AvKeyParameter::DigitKey)]);
+
+        assert_eq!(keybind.set_keys(vec![AvKey::Key(56), AvKey::Parameter(AvKeyParameter::DigitKey)]), Ok(()));
+        assert_eq!(keybind.keys(), &[AvKey::Key(56), AvKey::Parameter(AvKeyParameter::DigitKey)]);
+
+        let err = keybind.set_keys(vec![AvKey::Key(56), AvKey::Key(2)]).unwrap_err();
+        assert_eq!(err, OverrideError::ParameterMismatch { expected : vec![AvKeyParameter::DigitKey], found : vec![] });
+        assert_eq!(err.to_string(), "Expected the key parameters `{d}`, found none");
+        assert_eq!(keybind.keys(), &[AvKey::Key(56), AvKey::Parameter(AvKeyParameter::DigitKey)]);
+    }
+
+    #[test]
+    fn set_keys_checks_parameter_order() {
+        let (d, f) = (AvKey::Parameter(AvKeyParameter::DigitKey), AvKey::Parameter(AvKeyParameter::FunctionKey));
+        let mut keybind = TestKeybind::new("MoveToDesktop", &[AvKey::Key(125), d, f]);
+
+        assert_eq!(keybind.set_keys(vec![AvKey::Key(56), d, f]), Ok(()));
+        assert_eq!(keybind.set_keys(vec![AvKey::Key(125), f, d]), Err(OverrideError::ParameterMismatch {
+            expected : vec![AvKeyParameter::DigitKey, AvKeyParameter::FunctionKey],
+            found    : vec![AvKeyParameter::FunctionKey, AvKeyParameter::DigitKey],
+        }));
+        assert!(keybind.set_keys(vec![AvKey::Key(125), d]).is_err());
+        assert_eq!(keybind.keys(), &[AvKey::Key(56), d, f]);
+    }
+
+    #[test]
+    fn parameter_introspection() {
+        let keybind = TestKeybind::new("SwitchTab", &[AvKey::Key(29), AvKey::Parameter(AvKeyParameter::DigitKey)]);
+        assert!(keybind.has_parameters());
+        assert_eq!(keybind.parameter_count(), 1);
+
+        let keybind = TestKeybind::new("AvSearch", &[AvKey::Key(125), AvKey::Key(57)]);
+        assert!(!keybind.has_parameters());
+        assert_eq!(keybind.parameter_count(), 0);
+        assert_eq!(keybind.expand(), [[125, 57]]);
+    }
+}
@@ -0,0 +1,224 @@
+//!
+//! Keyboard layouts, mapping between physical keys and the characters they type.
+//!
+//! Keycodes are *physical*: code `30` is the key in the QWERTY-`A` position,
+//! whatever is printed on it. Anything going between keys and characters
+//! is *logical*, and depends on the user's layout.
+//!
+
+use crate::key::{AliasOverlay, KeyCode, PRINTABLE_KEYS};
+use crate::Key;
+
+///
+/// A keyboard layout, for the logical (character-based) key methods.
+/// 
+/// Only the unshifted ASCII characters are mapped.
+/// 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum Layout {
+    ///
+    /// US QWERTY, the default.
+    /// 
+    #[default]
+    UsQwerty,
+
+    ///
+    /// US Dvorak.
+    /// 
+    Dvorak,
+}
+
+// Characters typed by each of the `PRINTABLE_KEYS`, in the same order.
+const US_QWERTY : [char; 48] = [
+    '1', '2', '3', '4', '5', '6', '7', '8', '9', '0', '-', '=',
+    'q', 'w', 'e', 'r', 't', 'y', 'u', 'i', 'o', 'p', '[', ']',
+    'a', 's', 'd', 'f', 'g', 'h', 'j', 'k', 'l', ';', '\'', '`',
+    '\\', 'z', 'x', 'c', 'v', 'b', 'n', 'm', ',', '.', '/', ' ',
+];
+
+const DVORAK : [char; 48] = [
+    '1', '2', '3', '4', '5', '6', '7', '8', '9', '0', '[', ']',
+    '\'', ',', '.', 'p', 'y', 'f', 'g', 'c', 'r', 'l', '/', '=',
+    'a', 'o', 'e', 'u', 'i', 'd', 'h', 't', 'n', 's', '-', '`',
+    '\\', ';', 'q', 'j', 'k', 'x', 'b', 'm', 'w', 'v', 'z', ' ',
+];
+
+impl Layout {
+    const fn chars(&self) -> &'static [char; 48] {
+        match self {
+            Layout::UsQwerty => &US_QWERTY,
+            Layout::Dvorak => &DVORAK,
+        }
+    }
+
+    ///
+    /// Returns the (unshifted) character typed by a key in this layout.
+    /// 
+    /// ### Example
+    /// ```ignore
+    /// assert_eq!(Layout::UsQwerty.char_of(30), Some('a'));
+    /// assert_eq!(Layout::Dvorak.char_of(31), Some('o'));
+    /// ```
+    /// 
+    pub const fn char_of(&self, key : KeyCode) -> Option<char> {
+        let chars = self.chars();
+        let mut i = 0;
+        while i < PRINTABLE_KEYS.len() {
+            if PRINTABLE_KEYS[i] == key {
+                return Some(chars[i]);
+            }
+            i += 1;
+        }
+        None
+    }
+
+    ///
+    /// Returns the key which types a character in this layout.
+    /// 
+    /// Uppercase letters map to the same key as lowercase ones;
+    /// other shifted characters (e.g. `'!'`) have no key.
+    /// 
+    pub const fn code_of(&self, ch : char) -> Option<KeyCode> {
+        let ch = ch.to_ascii_lowercase();
+        let chars = self.chars();
+        let mut i = 0;
+        while i < chars.len() {
+            if chars[i] == ch {
+                return Some(PRINTABLE_KEYS[i]);
+            }
+            i += 1;
+        }
+        None
+    }
+}
+
+///
+/// Key names for a layout, overriding the built-in (QWERTY) ones, so keybinds
+/// can be written by what is printed on the keys: on AZERTY, `Ctrl+A` is the key
+/// in the QWERTY-`Q` position (code `16`).
+/// 
+/// Unlike a [Layout], this is about *names* (`A`), not characters (`'a'`):
+/// see [parse_keybind_in](crate::parse_keybind_in) and [Key::lookup_in].
+/// Names not in the keymap keep their built-in meaning.
+/// 
+/// ### Custom Keymaps
+/// Start from [Keymap::new] (or a built-in keymap) and add the names which differ:
+/// ```ignore
+/// let qwertz = Keymap::new("qwertz")
+///     .with("Z", Key::Y)
+///     .with("Y", Key::Z);
+/// 
+/// assert_eq!(parse_keybind_in("Ctrl+Z", &qwertz)?, [AvKey::EitherSide(29), AvKey::Key(21)]);
+/// ```
+/// 
+#[derive(Debug, Clone, Default)]
+pub struct Keymap {
+    name    : String,
+    aliases : AliasOverlay,
+}
+
+impl Keymap {
+    ///
+    /// An empty keymap, i.e. the built-in names.
+    /// 
+    pub fn new(name : impl Into<String>) -> Self {
+        Self { name : name.into(), aliases : AliasOverlay::new() }
+    }
+
+    ///
+    /// US QWERTY, which the built-in names already follow.
+    /// 
+    pub fn qwerty() -> Self {
+        Self::new("qwerty")
+    }
+
+    ///
+    /// French AZERTY: `A`/`Q` and `Z`/`W` swapped, and `M` beside `L`.
+    /// 
+    pub fn azerty() -> Self {
+        Self::new("azerty")
+            .with("A", Key::Q)
+            .with("Z", Key::W)
+            .with("Q", Key::A)
+            .with("M", Key::Semicolon)
+            .with("W", Key::Z)
+    }
+
+    ///
+    /// Returns the built-in keymap with this name (`"qwerty"`, `"azerty"`),
+    /// e.g. from a config file.
+    /// 
+    pub fn builtin(name : &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "qwerty" => Some(Self::qwerty()),
+            "azerty" => Some(Self::azerty()),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Names a key, replacing the built-in (or any previous) meaning of the name.
+    /// 
+    pub fn insert(&mut self, name : impl Into<String>, key : impl Into<KeyCode>) {
+        self.aliases.insert(name, key);
+    }
+
+    ///
+    /// Builder form of [Keymap::insert].
+    /// 
+    pub fn with(mut self, name : impl Into<String>, key : impl Into<KeyCode>) -> Self {
+        self.insert(name, key);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    ///
+    /// The names this keymap overrides, for [parse_keybind_with](crate::parse_keybind_with).
+    /// 
+    pub fn aliases(&self) -> &AliasOverlay {
+        &self.aliases
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_keybind_in, AvKey, Key, KeyCode};
+
+    use super::{Keymap, Layout};
+
+    fn code(key : Option<Key>) -> Option<KeyCode> {
+        key.map(Into::into)
+    }
+
+    #[test]
+    fn layouts() {
+        assert_eq!(Layout::UsQwerty.char_of(30), Some('a'));
+        assert_eq!(Layout::Dvorak.char_of(30), Some('a'));
+        assert_eq!(Layout::Dvorak.char_of(31), Some('o'));
+        assert_eq!(Layout::Dvorak.code_of('O'), Some(31));
+        assert_eq!(Layout::UsQwerty.char_of(29), None);
+
+        assert_eq!(code(Key::from_char('s', Layout::Dvorak)), Some(39));
+        assert_eq!(Key::S.to_char(Layout::Dvorak), Some('o'));
+        assert_eq!(code(Key::from_char('s', Layout::UsQwerty)), Some(31));
+    }
+
+    #[test]
+    fn keymaps() {
+        let azerty = Keymap::azerty();
+        assert_eq!(parse_keybind_in("Ctrl+A", &azerty), Ok(vec![AvKey::EitherSide(29), AvKey::Key(16)]));
+        assert_eq!(parse_keybind_in("Ctrl+S", &azerty), Ok(vec![AvKey::EitherSide(29), AvKey::Key(31)]));
+        assert_eq!(parse_keybind_in("Ctrl+A", &Keymap::qwerty()), Ok(vec![AvKey::EitherSide(29), AvKey::Key(30)]));
+
+        assert_eq!(Key::lookup_in("M", &azerty), Some(Key::Semicolon));
+        assert_eq!(Key::lookup_in("Esc", &azerty), Some(Key::Escape));
+
+        let qwertz = Keymap::builtin("QWERTY").unwrap().with("Z", Key::Y);
+        assert_eq!(code(Key::lookup_in("Z", &qwertz)), Some(21));
+        assert!(Keymap::builtin("colemak").is_none());
+    }
+}
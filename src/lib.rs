@@ -6,13 +6,34 @@
 //! See rexeports for more information.
 //!
 
+// So the `::avkeys::` paths emitted by the macros also resolve in this crate.
+extern crate self as avkeys;
+
+mod dispatch;
 mod key;
+mod keybind;
+mod layout;
+mod registry;
+#[cfg(feature = "config")]
+pub mod config;
 
 use avkeys_common::AvKeyDiscrim;
+use key::either_side_code;
+pub use avkeys_common::TriggerMode;
 pub use avkeys_macros::AvKeybind;
-pub use key::{AvKey, AvKeyParameter, KeyCode};
+pub use keybind::{AvKeybind, KeybindInfo, OverrideError};
+#[cfg(feature = "inventory")]
+pub use keybind::KeybindRegistration;
+#[cfg(feature = "inventory")]
+#[doc(hidden)]
+pub use inventory;
+pub use registry::{Conflict, Registry};
+pub use dispatch::Dispatcher;
+pub use layout::{Keymap, Layout};
+pub use key::{AvKey, AvKeyParameter, CustomKeyParameter, KeyCategory, KeyCode, KeyRow, KeyCombo, KeyState, ModifierFlags, EV_KEY, ParseError, ParseErrorKind, AliasOverlay, parse_keybind, parse_keybind_with, parse_keybind_in, parse_key_names, to_macro_syntax, aliases_of, matches, matches_loose, matches_on_press, matches_with_mods, validate_for_keyboard, modifier_mask, pressed_modifier_mask, combo_from_pressed, format_combo, ColoredCombo, expand, same_keys, accepts_parameters, ascii_char, is_printable, is_modifier};
+#[cfg(feature = "emacs-interop")]
+pub use key::parse_emacs_keybind;
 use avkeys_macros::keycodes;
-use colored::Colorize;
 
 #[cfg(feature = "parsing")]
 pub use ::avkeys_common::{ParsedKey, ParsedKeyDisc, ParsedKeybind,};
@@ -36,59 +57,59 @@ keycodes! {
     //! 
     
     Escape      => 1     match [Esc],
-    Digit1      => 2     match ['1', Dig1],
-    Digit2      => 3     match ['2', Dig2],
-    Digit3      => 4     match ['3', Dig3],
-    Digit4      => 5     match ['4', Dig4],
-    Digit5      => 6     match ['5', Dig5],
-    Digit6      => 7     match ['6', Dig6],
-    Digit7      => 8     match ['7', Dig7],
-    Digit8      => 9     match ['8', Dig8],
-    Digit9      => 10    match ['9', Dig9],
-    Digit0      => 11    match ['0', Dig0],
+    Digit1      => 2     match ['1', Dig1] #[param(digit = 1)],
+    Digit2      => 3     match ['2', Dig2] #[param(digit = 2)],
+    Digit3      => 4     match ['3', Dig3] #[param(digit = 3)],
+    Digit4      => 5     match ['4', Dig4] #[param(digit = 4)],
+    Digit5      => 6     match ['5', Dig5] #[param(digit = 5)],
+    Digit6      => 7     match ['6', Dig6] #[param(digit = 6)],
+    Digit7      => 8     match ['7', Dig7] #[param(digit = 7)],
+    Digit8      => 9     match ['8', Dig8] #[param(digit = 8)],
+    Digit9      => 10    match ['9', Dig9] #[param(digit = 9)],
+    Digit0      => 11    match ['0', Dig0] #[param(digit = 0)],
     Minus		=> 12    match ['-'],
-    Equal		=> 13    match ['='],
+    Equal		=> 13    match ['=', '+', Plus],
     Backspace   => 14    ,
     Tab			=> 15    match ['↹'],
     
-    Q           => 16    ,
-    W           => 17    ,
-    E           => 18    ,
-    R           => 19    ,
-    T           => 20    ,
-    Y           => 21    ,
-    U           => 22    ,
-    I           => 23    ,
-    O           => 24    ,
-    P           => 25    ,
+    Q           => 16    #[param(letter = 16)],
+    W           => 17    #[param(letter = 22)],
+    E           => 18    #[param(letter = 4)],
+    R           => 19    #[param(letter = 17)],
+    T           => 20    #[param(letter = 19)],
+    Y           => 21    #[param(letter = 24)],
+    U           => 22    #[param(letter = 20)],
+    I           => 23    #[param(letter = 8)],
+    O           => 24    #[param(letter = 14)],
+    P           => 25    #[param(letter = 15)],
     LeftBrace   => 26    match ['['],
     RightBrace  => 27    match [']'],
     Enter       => 28    ,
 
     LeftCtrl    => 29    match [Ctrl],
 
-    A			=> 30    ,
-    S			=> 31    ,
-    D			=> 32    ,
-    F			=> 33    ,
-    G			=> 34    ,
-    H			=> 35    ,
-    J			=> 36    ,
-    K			=> 37    ,
-    L			=> 38    ,
+    A			=> 30    #[param(letter = 0)],
+    S			=> 31    #[param(letter = 18)],
+    D			=> 32    #[param(letter = 3)],
+    F			=> 33    #[param(letter = 5)],
+    G			=> 34    #[param(letter = 6)],
+    H			=> 35    #[param(letter = 7)],
+    J			=> 36    #[param(letter = 9)],
+    K			=> 37    #[param(letter = 10)],
+    L			=> 38    #[param(letter = 11)],
     Semicolon   => 39    match [';'],
     Apostrophe  => 40    match ['\''],
     Grave       => 41    match ['`'],
     LeftShift   => 42    match [Shift],
     BackSlash   => 43    , // TODO: @Sammy99jsp add the character for this
 
-    Z           =>  44   ,
-    X           =>  45   ,
-    C           =>  46   ,
-    V           =>  47   ,
-    B           =>  48   ,
-    N           =>  49   ,
-    M           =>  50   ,
+    Z           =>  44    #[param(letter = 25)],
+    X           =>  45    #[param(letter = 23)],
+    C           =>  46    #[param(letter = 2)],
+    V           =>  47    #[param(letter = 21)],
+    B           =>  48    #[param(letter = 1)],
+    N           =>  49    #[param(letter = 13)],
+    M           =>  50    #[param(letter = 12)],
     Comma       =>  51   match [','],
     Dot         =>  52   match ['.'],
     Slash       =>	53   match ['/'],
@@ -98,16 +119,16 @@ keycodes! {
     Space       =>	57  ,
     CapsLock    =>	58  ,
 
-    F1          =>	59  ,
-    F2          =>	60  ,
-    F3          =>	61  ,
-    F4          =>	62  ,
-    F5          =>	63  ,
-    F6          =>	64  ,
-    F7          =>	65  ,
-    F8          =>	66  ,
-    F9          =>	67  ,
-    F10         =>	68  ,
+    F1          =>	59    #[param(function = 0)],
+    F2          =>	60    #[param(function = 1)],
+    F3          =>	61    #[param(function = 2)],
+    F4          =>	62    #[param(function = 3)],
+    F5          =>	63    #[param(function = 4)],
+    F6          =>	64    #[param(function = 5)],
+    F7          =>	65    #[param(function = 6)],
+    F8          =>	66    #[param(function = 7)],
+    F9          =>	67    #[param(function = 8)],
+    F10         =>	68    #[param(function = 9)],
     NumLock     =>	69  ,
     ScrollLock  =>	70  ,
 
@@ -125,17 +146,31 @@ keycodes! {
     KeyPad0     =>	82  ,
     KeyPadDot   =>  83  ,
 
-    /* Keys 85..=86 Omitted */
+    ///
+    /// Half-width/full-width toggle (JIS keyboards only).
+    /// 
+    ZenkakuHankaku  =>  85 match [Zenkaku],
+
+    /* Key 86 Omitted */
 
-    F11			=>  87  ,
-    F12			=>  88  ,
+    F11			=>  87    #[param(function = 10)],
+    F12			=>  88    #[param(function = 11)],
+
+    /* JIS keyboards only: these may not exist on other keyboards. */
+
+    Ro          =>  89  ,
+    Katakana    =>  90  ,
+    Hiragana    =>  91  ,
+    Henkan      =>  92  ,
+    KatakanaHiragana => 93 match [Kana],
+    Muhenkan    =>  94  ,
+    KeyPadJpComma => 95 ,
 
-    /* Keys 89..=95 Omitted */
     KeyPadEnter =>	96  ,
     RightCtrl   =>	97  ,
     KeyPadSlash =>	98  ,
     
-    SysRq       =>	99  ,
+    SysRq       =>	99  match [PrintScreen, PrtSc],
     RightAlt    =>	100 ,
 
     /* Key 101 Omitted */
@@ -149,7 +184,7 @@ keycodes! {
     DownArrow   =>	108 ,
     PageDown    =>	109 ,
     Insert      =>	110 ,
-    Delete      =>	111 ,
+    Delete      =>	111 match [Del],
 
     Macro       =>  112 ,
     Mute        =>  113 ,
@@ -162,13 +197,18 @@ keycodes! {
     Power       =>  116 ,	
     KeyPadEqual =>  117 ,
     KeyPadPlusMinus =>  118,
-    Pause       =>  119 ,
+    Pause       =>  119 match [Break],
 
     /* Key 120 Omitted */
 
     KeyPadComma =>  121 ,
 
-    /* Keys 122..=124 Omitted */
+    /* Keys 122..=123 Omitted */
+
+    ///
+    /// Yen sign key (JIS keyboards only).
+    /// 
+    Yen         =>  124 match ['¥'],
 
     LeftMeta    =>  125 match [Meta, Logo, Win],
     RightMeta   =>  126 ,
@@ -185,29 +225,355 @@ keycodes! {
     /// 
     Menu        =>  139 ,
 
+    ///
+    /// AL Calculator
+    /// 
+    Calculator  =>  140 match [Calc],
+
     /* Key 141 Omitted */
     Sleep       =>  142 ,
     Wakeup      =>  143 ,
 
-    /* Keys 144..=152 */
+    /* Keys 144..=149 Omitted */
+
+    ///
+    /// AL Internet Browser
+    /// 
+    Browser     =>  150 match [WWW],
+
+    /* Keys 151..=152 Omitted */
 
     ///
     /// Display orientation for e.g. tablets.
     /// 
     RotateDisplay => 153 match [Direction],
 
-    /* Keys 154..=162 Omitted */
+    /* Keys 154 Omitted */
+
+    Mail          => 155,
+
+    ///
+    /// AC Bookmarks
+    /// 
+    Bookmarks     => 156,
+    Computer      => 157 match [MyComputer],
+
+    ///
+    /// AC Back (e.g. in a browser).
+    /// 
+    BrowserBack   => 158,
+
+    ///
+    /// AC Forward (e.g. in a browser).
+    /// 
+    BrowserForward => 159,
+
+    /* Key 160 Omitted */
+
+    Eject         => 161 match [EjectCD],
+
+    /* Key 162 Omitted */
 
     NextSong      => 163,
     PlayPause     => 164,
     PreviousSong  => 165,
+    MediaStop     => 166 match [StopCD],
+    Record        => 167,
+    Rewind        => 168,
+
+    /* Keys 169..=170 Omitted */
+
+    ///
+    /// AL Consumer Control Configuration
+    /// 
+    Config        => 171,
+
+    ///
+    /// AC Home (e.g. a browser's home page).
+    /// 
+    HomePage      => 172,
+
+    ///
+    /// AC Refresh
+    /// 
+    Refresh       => 173,
+
+    /* Keys 174..=199 Omitted */
+
+    MediaPlay     => 200 match [PlayCD],
+    MediaPause    => 201 match [PauseCD],
+
+    /* Keys 202..=207 Omitted */
+
+    FastForward   => 208,
+
+    /* Keys 209..=211 Omitted */
+
+    Camera        => 212,
+
+    /* Keys 213..=214 Omitted */
+
+    Email         => 215,
 
-    /* Keys 166..=223 Omitted */
+    /* Key 216 Omitted */
+
+    Search        => 217,
+
+    /* Keys 218..=223 Omitted */
 
     BrightnessDown  =>	224,
     BrightnessUp    =>	225,
+    Media           =>	226,
+
+    /* Keys 227..=247 Omitted */
+
+    ///
+    /// Mute / unmute the microphone.
+    /// 
+    MicMute         =>	248 match [MuteMic],
+
+    /* Mouse buttons (BTN_LEFT..=BTN_TASK) */
+    MouseLeft     => 0x110 match [LeftClick],
+    MouseRight    => 0x111 match [RightClick],
+    MouseMiddle   => 0x112 match [MiddleClick],
+    MouseSide     => 0x113 ,
+    MouseExtra    => 0x114 ,
+    MouseForward  => 0x115 ,
+    MouseBack     => 0x116 ,
+    MouseTask     => 0x117 ,
+
+    /* Keys 0x118..=0x16b Omitted */
+
+    ///
+    /// AC Favorites
+    /// 
+    Favorites     => 0x16c ,
+}
+
+impl Key {
+    ///
+    /// Returns the `(type, code, value)` of an input event
+    /// for this key, ready to be written into an `input_event`
+    /// (e.g. through uinput).
+    /// 
+    /// ### Example
+    /// ```ignore
+    /// assert_eq!(Key::A.to_event(KeyState::Pressed), (EV_KEY, 30, 1));
+    /// ```
+    /// 
+    pub fn to_event(&self, state : KeyState) -> (u16, u16, i32) {
+        let code : KeyCode = (*self).into();
+        (EV_KEY, code as u16, state as i32)
+    }
+
+    ///
+    /// Whether this (pressed) key satisfies a key in a combination:
+    /// the same fixed key, or a member of a key parameter.
+    /// 
+    /// Unlike `==`, this checks key parameter membership:
+    /// `Key::Digit5.matches(&AvKey::Parameter(AvKeyParameter::DigitKey))` is `true`.
+    /// 
+    pub fn matches(&self, key : &AvKey) -> bool {
+        AvKey::Key((*self).into()).matches(key)
+    }
+
+    ///
+    /// This key's code as a single byte, for compact storage or transmission,
+    /// or `None` if the code is above 255 (rare: only some media and
+    /// special-purpose keys are).
+    /// 
+    /// See [Key::from_u8].
+    /// 
+    pub fn to_u8(&self) -> Option<u8> {
+        let code : KeyCode = (*self).into();
+        u8::try_from(code).ok()
+    }
+
+    ///
+    /// The key (its primary variant) with the given code,
+    /// as written by [Key::to_u8].
+    /// 
+    pub fn from_u8(code : u8) -> Option<Self> {
+        Self::try_from(code as KeyCode).ok()
+    }
+
+    ///
+    /// The same modifier on the other side of the keyboard
+    /// (`LeftAlt` for `RightAlt`, and vice versa), or `None` for non-modifiers.
+    /// 
+    pub fn other_side(&self) -> Option<Self> {
+        match self {
+            Key::LeftCtrl | Key::Ctrl => Some(Key::RightCtrl),
+            Key::RightCtrl => Some(Key::LeftCtrl),
+            Key::LeftShift | Key::Shift => Some(Key::RightShift),
+            Key::RightShift => Some(Key::LeftShift),
+            Key::LeftAlt | Key::Alt => Some(Key::RightAlt),
+            Key::RightAlt => Some(Key::LeftAlt),
+            Key::LeftMeta | Key::Meta | Key::Logo | Key::Win => Some(Key::RightMeta),
+            Key::RightMeta => Some(Key::LeftMeta),
+            _ => None,
+        }
+    }
+
+    ///
+    /// The keypad key typing the same as this one (`KeyPad7` for `Digit7`),
+    /// and vice versa, or `None` for keys without one.
+    /// 
+    /// `KeyPadPlus` maps to `Equal` (typing `+` with Shift),
+    /// though `Equal` maps back to `KeyPadEqual`.
+    /// 
+    pub fn keypad_equivalent(&self) -> Option<Self> {
+        match self {
+            Key::Digit1 | Key::Dig1 => Some(Key::KeyPad1),
+            Key::Digit2 | Key::Dig2 => Some(Key::KeyPad2),
+            Key::Digit3 | Key::Dig3 => Some(Key::KeyPad3),
+            Key::Digit4 | Key::Dig4 => Some(Key::KeyPad4),
+            Key::Digit5 | Key::Dig5 => Some(Key::KeyPad5),
+            Key::Digit6 | Key::Dig6 => Some(Key::KeyPad6),
+            Key::Digit7 | Key::Dig7 => Some(Key::KeyPad7),
+            Key::Digit8 | Key::Dig8 => Some(Key::KeyPad8),
+            Key::Digit9 | Key::Dig9 => Some(Key::KeyPad9),
+            Key::Digit0 | Key::Dig0 => Some(Key::KeyPad0),
+            Key::Minus => Some(Key::KeyPadMinus),
+            Key::Equal | Key::Plus => Some(Key::KeyPadEqual),
+            Key::Dot => Some(Key::KeyPadDot),
+            Key::Slash => Some(Key::KeyPadSlash),
+            Key::Comma => Some(Key::KeyPadComma),
+            Key::Enter => Some(Key::KeyPadEnter),
+            Key::KeyPad1 => Some(Key::Digit1),
+            Key::KeyPad2 => Some(Key::Digit2),
+            Key::KeyPad3 => Some(Key::Digit3),
+            Key::KeyPad4 => Some(Key::Digit4),
+            Key::KeyPad5 => Some(Key::Digit5),
+            Key::KeyPad6 => Some(Key::Digit6),
+            Key::KeyPad7 => Some(Key::Digit7),
+            Key::KeyPad8 => Some(Key::Digit8),
+            Key::KeyPad9 => Some(Key::Digit9),
+            Key::KeyPad0 => Some(Key::Digit0),
+            Key::KeyPadMinus => Some(Key::Minus),
+            Key::KeyPadEqual | Key::KeyPadPlus => Some(Key::Equal),
+            Key::KeyPadDot => Some(Key::Dot),
+            Key::KeyPadSlash => Some(Key::Slash),
+            Key::KeyPadComma => Some(Key::Comma),
+            Key::KeyPadEnter => Some(Key::Enter),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Whether this key is a modifier: Ctrl, Shift, Alt, or Meta (either side).
+    /// 
+    pub fn is_modifier(&self) -> bool {
+        is_modifier((*self).into())
+    }
+
+    ///
+    /// Returns the [category](KeyCategory) of this key.
+    /// 
+    pub fn category(&self) -> KeyCategory {
+        KeyCategory::of((*self).into())
+    }
+
+    ///
+    /// Returns the physical [row](KeyRow) of this key, or `None`
+    /// for keys outside the main block (navigation, keypad, media).
+    /// 
+    pub fn row(&self) -> Option<KeyRow> {
+        KeyRow::of((*self).into())
+    }
+
+    ///
+    /// Iterates over every key in a category, in keycode order.
+    /// 
+    /// ### Example
+    /// ```ignore
+    /// let names = Key::in_category(KeyCategory::Navigation)
+    ///     .map(|k| k.to_string())
+    ///     .collect::<Vec<_>>();
+    /// ```
+    /// 
+    pub fn in_category(category : KeyCategory) -> impl Iterator<Item = Key> {
+        Key::ALL.iter()
+            .copied()
+            .filter(move |k| k.category() == category)
+    }
+
+    ///
+    /// Returns the key which types a character in a layout.
+    /// 
+    /// This is *logical*, so layout-dependent: unlike `Key::lookup('a')`,
+    /// which always assumes US QWERTY.
+    /// 
+    /// ### Example
+    /// ```ignore
+    /// assert_eq!(Key::from_char('o', Layout::Dvorak), Some(Key::S));
+    /// ```
+    /// 
+    pub fn from_char(ch : char, layout : Layout) -> Option<Key> {
+        layout.code_of(ch).and_then(Key::lookup)
+    }
+
+    ///
+    /// Like [Key::lookup] by name, with the names of a [Keymap]:
+    /// `Key::lookup_in("A", &Keymap::azerty())` is [Key::Q].
+    /// 
+    pub fn lookup_in(name : &str, keymap : &Keymap) -> Option<Key> {
+        match keymap.aliases().get(name) {
+            Some(code) => Key::lookup(code),
+            None => Key::lookup(name),
+        }
+    }
+
+    ///
+    /// Returns the (unshifted) character this key types in a layout.
+    /// 
+    /// This is *logical*, so layout-dependent.
+    /// 
+    pub fn to_char(&self, layout : Layout) -> Option<char> {
+        layout.char_of((*self).into())
+    }
+}
+
+///
+/// Keys are compared by keycode, so aliases are equal (`Key::Ctrl == Key::LeftCtrl`),
+/// and ordering is stable however the keycode table is arranged.
+/// 
+impl PartialEq for Key {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Key {}
+
+impl PartialOrd for Key {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Key {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        let (l, r) : (KeyCode, KeyCode) = ((*self).into(), (*other).into());
+        l.cmp(&r)
+    }
+}
 
-    /* Keys 226..=248 Omitted */
+///
+/// A [Key] equals an [AvKey] only if it is the same fixed key,
+/// see [Key::matches] for key parameter membership.
+/// 
+impl PartialEq<AvKey> for Key {
+    fn eq(&self, other: &AvKey) -> bool {
+        let code : KeyCode = (*self).into();
+        matches!(other, AvKey::Key(k) if *k == code)
+    }
+}
+
+impl PartialEq<Key> for AvKey {
+    fn eq(&self, other: &Key) -> bool {
+        other == self
+    }
 }
 
 impl Into<AvKey> for Key {
@@ -226,40 +592,95 @@ impl ToString for Key {
         self.name()
             .iter()
             .min_by_key(|n| n.len())
-            .unwrap()
-            .to_string()
+            .map(|n| n.to_string())
+            .unwrap_or_else(|| Into::<KeyCode>::into(*self).to_string())
     }
 }
 
-impl std::fmt::Display for AvKey {
+///
+/// Error from naming a keycode which has no name (see [AvKey::to_name_string]).
+/// 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownKey(pub KeyCode);
+
+impl std::fmt::Display for UnknownKey {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", match self {
+        write!(f, "Keycode `{}` has no name", self.0)
+    }
+}
+
+impl std::error::Error for UnknownKey {}
+
+impl AvKey {
+    ///
+    /// Returns this key's plain name, e.g. `Ctrl`, `{d}` or `!Shift`:
+    /// never colored, unlike its [Display](std::fmt::Display), so fit for
+    /// config files and other machine-read text.
+    /// 
+    /// Modifiers on one side are named by side (`LeftCtrl`), since
+    /// the side-less names stand for [either side](AvKey::EitherSide).
+    /// 
+    pub fn to_name_string(&self) -> Result<String, UnknownKey> {
+        match self {
             AvKey::Key(k) => Key::lookup(*k)
-                .and_then(|k|{
-                    Some(k.to_string().blue())
+                .map(|k| match k.to_string() {
+                    name if either_side_code(&name).is_some() => format!("{k:?}"),
+                    name => name,
                 })
-                .unwrap_or("ERR".strikethrough().red()),
-            AvKey::Parameter(p) => format!("{{{}}}", p.to_string()).yellow(),
-        })
+                .ok_or(UnknownKey(*k)),
+            AvKey::EitherSide(k) => Key::lookup(*k)
+                .map(|k| k.to_string())
+                .ok_or(UnknownKey(*k)),
+            AvKey::Parameter(p) => Ok(p.to_string()),
+            AvKey::NotHeld(k) => Key::lookup(*k)
+                .map(|k| format!("!{}", k.to_string()))
+                .ok_or(UnknownKey(*k)),
+        }
+    }
+}
+
+///
+/// Displays as the key's name (`!`-prefixed if [not held](AvKey::NotHeld)),
+/// or `ERR` for keycodes without one.
+/// 
+/// With the `color` feature (on by default), the output is colored by kind
+/// unless `NO_COLOR` is set or stdout isn't a terminal (see [colored]).
+/// 
+impl std::fmt::Display for AvKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = self.to_name_string();
+
+        #[cfg(feature = "color")]
+        {
+            use colored::Colorize;
+
+            write!(f, "{}", match (self, name) {
+                (_, Err(_)) => "ERR".strikethrough().red(),
+                (AvKey::Key(_) | AvKey::EitherSide(_), Ok(name)) => name.blue(),
+                (AvKey::Parameter(_), Ok(name)) => name.yellow(),
+                (AvKey::NotHeld(_), Ok(name)) => name.magenta(),
+            })
+        }
+
+        #[cfg(not(feature = "color"))]
+        write!(f, "{}", name.as_deref().unwrap_or("ERR"))
     }
 }
 
+///
+/// Same as [AvKey::to_name_string].
+/// 
 impl TryInto<String> for AvKey {
-    type Error = ();
+    type Error = UnknownKey;
 
     fn try_into(self) -> Result<String, Self::Error> {
-        match self {
-            AvKey::Key(k) => Key::lookup(k)
-                .map(|k| Ok(k.to_string()))
-                .unwrap_or(Err(())),
-            AvKey::Parameter(p) => Ok(p.to_string()),
-        }
+        self.to_name_string()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::AvKey;
+    use crate::{matches, parse_keybind, AvKey, AvKeyParameter, KeyCategory, KeyCode, KeyRow, KeyState, UnknownKey};
 
     use crate::Key;
 
@@ -268,4 +689,270 @@ mod tests {
         let k : AvKey = Key::lookup("LeftCtrl").unwrap().into();
         println!("{k}");
     }
+
+    #[test]
+    fn plus_alias() {
+        let equal : KeyCode = Key::Equal.into();
+        let plus  : KeyCode = Key::lookup("Plus").unwrap().into();
+        let plus_char : KeyCode = Key::lookup('+').unwrap().into();
+        let minus : KeyCode = Key::lookup("Minus").unwrap().into();
+
+        assert_eq!(plus, equal);
+        assert_eq!(plus_char, equal);
+        assert_eq!(minus, 12);
+    }
+
+    #[test]
+    fn const_code() {
+        const _ : () = assert!(Key::A.code() == 30);
+        const _ : () = assert!(Key::Ctrl.code() == Key::LeftCtrl.code());
+
+        assert_eq!(Key::Escape.code(), Into::<KeyCode>::into(Key::Escape));
+    }
+
+    #[test]
+    fn key_ord_by_code() {
+        assert!(Key::A < Key::S);
+        assert!(Key::A < Key::B);
+        assert_eq!(Key::Ctrl, Key::LeftCtrl);
+
+        let mut keys = vec![Key::Space, Key::Escape, Key::Yen, Key::A, Key::Ctrl];
+        keys.sort();
+        assert_eq!(keys, [Key::Escape, Key::LeftCtrl, Key::A, Key::Space, Key::Yen]);
+    }
+
+    #[test]
+    fn jis_keys() {
+        let code = |name : &str| Key::lookup(name).map(|k| -> KeyCode { k.into() });
+
+        assert_eq!(code("ZenkakuHankaku"), Some(85));
+        assert_eq!(code("Ro"), Some(89));
+        assert_eq!(code("Katakana"), Some(90));
+        assert_eq!(code("Hiragana"), Some(91));
+        assert_eq!(code("Henkan"), Some(92));
+        assert_eq!(code("Kana"), Some(93));
+        assert_eq!(code("Muhenkan"), Some(94));
+        assert_eq!(code("KeyPadJpComma"), Some(95));
+        assert_eq!(code("Yen"), Some(124));
+        assert_eq!(Key::lookup('¥').map(|k| -> KeyCode { k.into() }), Some(124));
+    }
+
+    #[test]
+    fn function_key_category() {
+        let codes = Key::in_category(KeyCategory::Function)
+            .map(|k| -> KeyCode { k.into() })
+            .collect::<Vec<_>>();
+
+        assert_eq!(codes, [59, 60, 61, 62, 63, 64, 65, 66, 67, 68, 87, 88]);
+        assert_eq!(Key::in_category(KeyCategory::Letter).count(), 26);
+    }
+
+    #[test]
+    fn lookup_and_names() {
+        assert_eq!(Key::lookup("Ctrl"), Some(Key::LeftCtrl));
+        assert_eq!(Key::lookup(29), Some(Key::LeftCtrl));
+        assert_eq!(Key::lookup_const("Ctrl"), Some(Key::LeftCtrl));
+        assert!(Key::LeftCtrl.name().contains(&"Ctrl"));
+        assert!(Key::LeftCtrl.name().contains(&"LeftCtrl"));
+    }
+
+    #[test]
+    fn lookup_every_name() {
+        for key in Key::ALL {
+            for name in key.name() {
+                assert_eq!(Key::lookup(*name), Some(*key), "{name}");
+                assert_eq!(Key::code_of(name), Some(Into::<KeyCode>::into(*key)), "{name}");
+            }
+        }
+
+        for name in ["", "Ctr", "Ctrlx", "ctrl", "Zzz", "\u{0}"] {
+            assert_eq!(Key::lookup(name), None, "{name}");
+        }
+    }
+
+    #[test]
+    fn code_of_const() {
+        const ESC : KeyCode = Key::code_of("Esc").unwrap();
+
+        assert_eq!(ESC, 1);
+        assert_eq!(Key::code_of("Ctrl"), Some(29));
+        assert_eq!(Key::code_of("+"), Key::lookup('+').map(Into::into));
+        assert_eq!(Key::code_of("Nope"), None);
+    }
+
+    #[test]
+    fn key_try_from_code() {
+        assert_eq!(Key::try_from(29), Ok(Key::LeftCtrl));
+        assert_eq!(Key::try_from(0x1e), Ok(Key::A));
+        assert_eq!(Key::try_from(0xfff), Err(0xfff));
+    }
+
+    #[test]
+    fn to_name_string() {
+        assert_eq!(AvKey::EitherSide(29).to_name_string().as_deref(), Ok("Ctrl"));
+        assert_eq!(AvKey::Key(29).to_name_string().as_deref(), Ok("LeftCtrl"));
+        assert_eq!(AvKey::NotHeld(42).to_name_string().as_deref(), Ok("!Shift"));
+        assert_eq!(AvKey::Parameter(AvKeyParameter::DigitKey).to_name_string().as_deref(), Ok("{d}"));
+        assert_eq!(AvKey::Key(199).to_name_string(), Err(UnknownKey(199)));
+        assert_eq!(UnknownKey(199).to_string(), "Keycode `199` has no name");
+
+        let name : Result<String, _> = AvKey::NotHeld(250).try_into();
+        assert_eq!(name, Err(UnknownKey(250)));
+    }
+
+    #[test]
+    fn key_u8_roundtrip() {
+        assert_eq!(Key::A.to_u8(), Some(30));
+        assert_eq!(Key::from_u8(30), Some(Key::A));
+        assert_eq!(Key::from_u8(Key::LeftCtrl.to_u8().unwrap()), Some(Key::LeftCtrl));
+        assert_eq!(Key::from_u8(0), None);
+    }
+
+    #[test]
+    fn key_names_literal_aliases() {
+        // Only aliased by a character.
+        assert_eq!(Key::Minus.name()[0], "Minus");
+        assert_eq!(Key::Minus.to_string(), "-");
+        assert_eq!(Key::Tab.name(), ["Tab", "↹"]);
+
+        assert_eq!(Key::Ctrl.name()[0], "LeftCtrl");
+        assert!(Key::ALL.iter().all(|k| !k.name().is_empty()));
+    }
+
+    #[test]
+    fn iter_keys() {
+        assert_eq!(Key::iter().count(), Key::KEY_COUNT);
+        assert_eq!(Key::iter().next(), Some(Key::Escape));
+        assert!(Key::iter().any(|k| k == Key::MouseLeft));
+
+        // Primary variants only.
+        let names = Key::iter().map(|k| format!("{k:?}")).collect::<Vec<_>>();
+        assert!(names.contains(&"LeftCtrl".to_string()));
+        assert!(!names.contains(&"Ctrl".to_string()));
+    }
+
+    #[test]
+    fn variant_names() {
+        assert_eq!(Key::VARIANT_NAMES.len(), Key::KEY_COUNT);
+        assert_eq!(Key::ALL.len(), Key::KEY_COUNT);
+        assert!(Key::VARIANT_NAMES.contains(&"Escape"));
+        assert!(!Key::VARIANT_NAMES.contains(&"Esc"));
+
+        let i = Key::VARIANT_NAMES.iter().position(|n| *n == "LeftCtrl").unwrap();
+        assert_eq!(Key::ALL[i], Key::LeftCtrl);
+    }
+
+    #[test]
+    fn media_keys() {
+        let codes = [
+            ("Calc", 140), ("WWW", 150), ("Mail", 155), ("Email", 215), ("Search", 217),
+            ("StopCD", 166), ("PlayCD", 200), ("Media", 226), ("MicMute", 248), ("Favorites", 0x16c),
+        ];
+
+        for (name, code) in codes {
+            let key = Key::lookup(name).unwrap();
+            assert_eq!(Into::<KeyCode>::into(key), code, "{name}");
+            assert_eq!(Key::try_from(code), Ok(key), "{name}");
+        }
+    }
+
+    #[test]
+    fn mouse_buttons() {
+        assert_eq!(Key::lookup("LeftClick"), Some(Key::MouseLeft));
+        assert_eq!(Key::try_from(0x111), Ok(Key::MouseRight));
+        assert_eq!(Key::MouseLeft.category(), KeyCategory::Mouse);
+        assert_eq!(Key::MouseLeft.to_u8(), None);
+        assert_eq!(Key::MouseLeft.row(), None);
+
+        let keys = parse_keybind("Logo+MouseLeft").unwrap();
+        assert_eq!(keys, [AvKey::EitherSide(125), AvKey::Key(0x110)]);
+        assert_eq!(matches(&keys, &[125, 0x110]), Some(vec![]));
+    }
+
+    #[test]
+    fn key_is_modifier() {
+        assert!(Key::LeftCtrl.is_modifier());
+        assert!(Key::RightMeta.is_modifier());
+        assert!(Key::Alt.is_modifier());
+        assert!(!Key::A.is_modifier());
+        assert!(!Key::CapsLock.is_modifier());
+        assert_eq!(Key::RightShift.category(), KeyCategory::Modifier);
+    }
+
+    #[test]
+    fn key_keypad_equivalent() {
+        assert_eq!(Key::Digit7.keypad_equivalent(), Some(Key::KeyPad7));
+        assert_eq!(Key::KeyPad7.keypad_equivalent(), Some(Key::Digit7));
+        assert_eq!(Key::Dig0.keypad_equivalent(), Some(Key::KeyPad0));
+        assert_eq!(Key::KeyPadEnter.keypad_equivalent(), Some(Key::Enter));
+        assert_eq!(Key::KeyPadPlus.keypad_equivalent(), Some(Key::Equal));
+        assert_eq!(Key::A.keypad_equivalent(), None);
+        assert_eq!(Key::KeyPadAsterisk.keypad_equivalent(), None);
+    }
+
+    #[test]
+    fn key_other_side() {
+        assert_eq!(Key::LeftAlt.other_side(), Some(Key::RightAlt));
+        assert_eq!(Key::RightMeta.other_side(), Some(Key::LeftMeta));
+        assert_eq!(Key::Shift.other_side(), Some(Key::RightShift));
+        assert_eq!(Key::A.other_side(), None);
+    }
+
+    #[test]
+    fn key_rows() {
+        assert_eq!(Key::F5.row(), Some(KeyRow::Function));
+        assert_eq!(Key::Grave.row(), Some(KeyRow::Number));
+        assert_eq!(Key::Q.row(), Some(KeyRow::Top));
+        assert_eq!(Key::Enter.row(), Some(KeyRow::Home));
+        assert_eq!(Key::RightShift.row(), Some(KeyRow::Bottom));
+        assert_eq!(Key::Space.row(), Some(KeyRow::Space));
+
+        assert_eq!(Key::Home.row(), None);
+        assert_eq!(Key::KeyPad1.row(), None);
+    }
+
+    #[test]
+    fn print_screen_aliases() {
+        let code = |name : &str| Key::lookup(name).map(|k| -> KeyCode { k.into() });
+
+        assert_eq!(code("PrintScreen"), Some(99));
+        assert_eq!(code("PrtSc"), Some(99));
+        assert_eq!(code("Break"), Some(119));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn key_serde_round_trip() {
+        let keys = [Key::LeftCtrl, Key::Ctrl, Key::A, Key::Digit1];
+        let json = serde_json::to_string(&keys).unwrap();
+
+        assert_eq!(json, r#"["LeftCtrl","LeftCtrl","A","Digit1"]"#);
+
+        let back : Vec<Key> = serde_json::from_str(&json).unwrap();
+        let codes = back.into_iter().map(Into::<KeyCode>::into).collect::<Vec<_>>();
+        assert_eq!(codes, [29, 29, 30, 2]);
+
+        let alias : Key = serde_json::from_str(r#""Ctrl""#).unwrap();
+        assert_eq!(Into::<KeyCode>::into(alias), 29);
+    }
+
+    #[test]
+    fn key_eq_avkey() {
+        let digits = AvKey::Parameter(AvKeyParameter::DigitKey);
+
+        assert!(Key::A == AvKey::Key(30));
+        assert!(AvKey::Key(30) == Key::A);
+        assert!(Key::A != AvKey::Key(31));
+
+        assert!(Key::Digit5 != digits);
+        assert!(Key::Digit5.matches(&digits));
+        assert!(!Key::A.matches(&digits));
+        assert!(Key::A.matches(&AvKey::Key(30)));
+    }
+
+    #[test]
+    fn key_to_event() {
+        assert_eq!(Key::A.to_event(KeyState::Pressed), (1, 30, 1));
+        assert_eq!(Key::A.to_event(KeyState::Released), (1, 30, 0));
+    }
 }
\ No newline at end of file
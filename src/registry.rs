@@ -0,0 +1,804 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::key::{either_side_mask, modifiers_fit, parse_logical_keybind};
+use crate::{expand, format_combo, is_modifier, matches, matches_loose, modifier_mask, parse_keybind, pressed_modifier_mask, AvKey, AvKeybind, KeyCode, KeyCombo, KeybindInfo, Layout, OverrideError, TriggerMode};
+#[cfg(feature = "inventory")]
+use crate::KeybindRegistration;
+
+///
+/// A registered keybind, with its precomputed modifier masks.
+/// 
+struct Entry<S> {
+    mask    : u8,
+
+    ///
+    /// Modifiers the keybind takes on either side (see [AvKey::EitherSide]),
+    /// by their left-side bits in `mask`.
+    /// 
+    either  : u8,
+    keybind : Box<dyn AvKeybind<S>>,
+
+    ///
+    /// Which of the keybind's keys were given by the character they type
+    /// (see [Registry::set_logical_override]), by position; empty if none were.
+    /// 
+    logical : Vec<bool>,
+}
+
+///
+/// Two registered keybinds which fire for the same keys
+/// (see [Registry::check_conflicts]).
+/// 
+/// Displays as a human-readable message, e.g.
+/// ``"`Ctrl+{d}` (SwitchTab) conflicts with `Ctrl+5` (QuickAction) for key `5`"``.
+/// 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    ///
+    /// [IDs](AvKeybind::id) of the keybinds, in registration order.
+    /// 
+    pub ids  : [&'static str; 2],
+    pub keys : [Vec<AvKey>; 2],
+
+    ///
+    /// A key both keybinds accept, though they don't both
+    /// name it: captured by a key parameter in at least one.
+    /// 
+    /// `None` when the keybinds have the same keys.
+    /// 
+    pub key  : Option<KeyCode>,
+}
+
+impl Conflict {
+    pub fn message(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl std::fmt::Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "`{}` ({}) conflicts with `{}` ({})",
+            format_combo(&self.keys[0], "+"), self.ids[0],
+            format_combo(&self.keys[1], "+"), self.ids[1],
+        )?;
+
+        match self.key {
+            Some(code) => write!(f, " for key `{}`", format_combo(&[AvKey::Key(code)], "+")),
+            None => Ok(()),
+        }
+    }
+}
+
+///
+/// ### Keybind Registry
+/// 
+/// A collection of keybinds, used to query, dispatch,
+/// and export them as a whole.
+/// 
+/// `S` is the state passed to the keybinds' callbacks (see [AvKeybind]).
+/// 
+pub struct Registry<S = ()> {
+    keybinds : Vec<Entry<S>>,
+}
+
+impl<S> Default for Registry<S> {
+    fn default() -> Self {
+        Self { keybinds : vec![] }
+    }
+}
+
+///
+/// Builds a [Registry] of the listed keybinds (declared
+/// with [#\[AvKeybind\]](avkeys_macros::AvKeybind)), in order.
+/// 
+/// Fails to compile when two of them have the [same](crate::same_keys)
+/// default keys, since which would run is then unspecified.
+/// 
+/// ### Example
+/// ```ignore
+/// let registry = keybinds! { AvSearch, PowerOptions, SwitchTab };
+/// ```
+/// 
+#[macro_export]
+macro_rules! keybinds {
+    (@check) => {};
+    (@check $first:ty $(, $rest:ty)*) => {
+        $(
+            const _ : () = assert!(
+                !$crate::same_keys(<$first>::DEFAULT_KEYS, <$rest>::DEFAULT_KEYS),
+                concat!("`", stringify!($first), "` and `", stringify!($rest), "` have the same default keys"),
+            );
+        )*
+        $crate::keybinds!(@check $($rest),*);
+    };
+    ($($keybind:ty),+ $(,)?) => {{
+        $crate::keybinds!(@check $($keybind),+);
+
+        let mut registry = $crate::Registry::default();
+        $( registry.register(<$keybind as ::std::default::Default>::default()); )+
+        registry
+    }};
+}
+
+impl Registry {
+    ///
+    /// Creates a registry of every keybind declared with
+    /// [#\[AvKeybind\]](avkeys_macros::AvKeybind) in the final binary.
+    /// 
+    /// Registration order is unspecified.
+    /// 
+    #[cfg(feature = "inventory")]
+    pub fn from_inventory() -> Self {
+        let mut registry = Self::new();
+        for registration in inventory::iter::<KeybindRegistration> {
+            registry.register_boxed((registration.constructor)());
+        }
+        registry
+    }
+}
+
+impl<S> Registry<S> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Adds a keybind to this registry.
+    /// 
+    pub fn register<K : AvKeybind<S> + 'static>(&mut self, keybind : K) {
+        self.register_boxed(Box::new(keybind));
+    }
+
+    fn register_boxed(&mut self, keybind : Box<dyn AvKeybind<S>>) {
+        self.keybinds.push(Entry {
+            mask    : modifier_mask(keybind.keys()),
+            either  : either_side_mask(keybind.keys()),
+            keybind,
+            logical : vec![],
+        });
+    }
+
+    ///
+    /// Iterates over all registered keybinds, in registration order.
+    /// 
+    pub fn iter(&self) -> impl Iterator<Item = &dyn AvKeybind<S>> {
+        self.keybinds.iter().map(|e| e.keybind.as_ref())
+    }
+
+    ///
+    /// Finds a registered keybind by its [ID](AvKeybind::id).
+    /// 
+    pub fn get(&self, id : &str) -> Option<&dyn AvKeybind<S>> {
+        self.iter().find(|k| k.id() == id)
+    }
+
+    ///
+    /// Sets (or with `None`, clears) the override of a registered keybind's keys.
+    /// 
+    /// The keys must have the same key parameters as the keybind's
+    /// (see [AvKeybind::set_keys]); otherwise, its current keys are kept.
+    /// 
+    /// Returns `Ok(false)` if no keybind has this ID.
+    /// 
+    pub fn set_override(&mut self, id : &str, keys : Option<Vec<AvKey>>) -> Result<bool, OverrideError> {
+        match self.keybinds.iter_mut().find(|e| e.keybind.id() == id) {
+            Some(e) => {
+                match keys {
+                    Some(keys) => e.keybind.set_keys(keys)?,
+                    None => e.keybind.reset(),
+                }
+                e.mask = modifier_mask(e.keybind.keys());
+                e.either = either_side_mask(e.keybind.keys());
+                e.logical.clear();
+                Ok(true)
+            },
+            None => Ok(false),
+        }
+    }
+
+    ///
+    /// Overrides a registered keybind's keys with a keybind string (see [parse_keybind])
+    /// whose quoted characters (`'a'`, `'/'`) stand for the key typing them on `layout`,
+    /// rather than a physical key.
+    /// 
+    /// Those keys follow the characters through [Registry::remap_layout],
+    /// while keys given by name or code stay put.
+    /// 
+    /// As with [Registry::set_override], the keys must have the same key
+    /// parameters as the keybind's. Returns `Ok(false)` if no keybind has this ID.
+    /// 
+    pub fn set_logical_override(&mut self, id : &str, s : &str, layout : Layout) -> Result<bool, OverrideError> {
+        let (keys, logical) = parse_logical_keybind(s, layout)?
+            .into_iter()
+            .unzip();
+
+        if !self.set_override(id, Some(keys))? {
+            return Ok(false);
+        }
+
+        if let Some(e) = self.keybinds.iter_mut().find(|e| e.keybind.id() == id) {
+            e.logical = logical;
+        }
+        Ok(true)
+    }
+
+    ///
+    /// Moves the keys given by character (see [Registry::set_logical_override])
+    /// to wherever their characters are on the `new` layout,
+    /// after the user switched to it from `old`.
+    /// 
+    /// Keys whose character isn't on the `new` layout stay put.
+    /// 
+    pub fn remap_layout(&mut self, old : Layout, new : Layout) {
+        for e in self.keybinds.iter_mut().filter(|e| e.logical.contains(&true)) {
+            let keys = e.keybind.keys()
+                .iter()
+                .zip(&e.logical)
+                .map(|(key, logical)| match (key, logical) {
+                    (AvKey::Key(code), true) => AvKey::Key(old.char_of(*code)
+                        .and_then(|c| new.code_of(c))
+                        .unwrap_or(*code)),
+                    (key, _) => *key,
+                })
+                .collect::<Vec<_>>();
+
+            *e.keybind.keys_override() = Some(keys);
+            e.mask = modifier_mask(e.keybind.keys());
+            e.either = either_side_mask(e.keybind.keys());
+        }
+    }
+
+    ///
+    /// Returns the keys of a registered keybind which depend on the layout:
+    /// those given by character (see [Registry::set_logical_override]),
+    /// which [move](Registry::remap_layout) when the layout changes.
+    /// 
+    /// Keys given by name or code are physical, so never returned;
+    /// nor is anything if no keybind has this ID.
+    /// 
+    /// Useful for warning users that a shortcut may move if they change layout.
+    /// 
+    pub fn layout_affected_keys(&self, id : &str) -> Vec<AvKey> {
+        self.keybinds.iter()
+            .find(|e| e.keybind.id() == id)
+            .map(|e| e.keybind.keys()
+                .iter()
+                .zip(&e.logical)
+                .filter(|(_, logical)| **logical)
+                .map(|(key, _)| *key)
+                .collect())
+            .unwrap_or_default()
+    }
+
+    ///
+    /// Finds the keybind bound to a combination string (e.g. `"Ctrl+A"`).
+    /// 
+    /// A concrete combination finds keybinds whose key parameters cover it
+    /// (`"Ctrl+5"` finds a `Ctrl+{d}` keybind), while a parameterized one
+    /// only finds keybinds with the same parameters (`"Ctrl+{d}"`).
+    /// 
+    /// Returns `None` if nothing matches, or the string does not parse.
+    /// 
+    pub fn lookup_combo(&self, s : &str) -> Option<&dyn AvKeybind<S>> {
+        let query = parse_keybind(s).ok()?;
+
+        let codes = query.iter()
+            .map(AvKey::key)
+            .collect::<Option<Vec<_>>>();
+
+        match codes {
+            Some(codes) => self.iter()
+                .find(|k| matches(k.keys(), &codes).is_some()),
+            None => self.iter().find(|k| same_combo(k.keys(), &query)),
+        }
+    }
+
+    ///
+    /// Lists what can be pressed next after a sequence's first combination
+    /// (e.g. `Ctrl+K`), for which-key style popups.
+    /// 
+    /// For each sequence keybind starting with `prefix`, returns the trigger key of
+    /// its next step (the last non-modifier key, see [matches_on_press](crate::matches_on_press)),
+    /// with the keybind itself; the whole next step is its [then](AvKeybind::then)`()[0]`.
+    /// 
+    pub fn continuations(&self, prefix : &[AvKey]) -> Vec<(AvKey, &dyn AvKeybind<S>)> {
+        self.iter()
+            .filter(|k| same_combo(k.keys(), prefix))
+            .filter_map(|k| {
+                let next = k.then().first()?.keys();
+                let trigger = next.iter()
+                    .filter(|k| k.not_held().is_none())
+                    .rfind(|k| !k.key().is_some_and(is_modifier))
+                    .or_else(|| next.last())?;
+
+                Some((*trigger, k))
+            })
+            .collect()
+    }
+
+    ///
+    /// Finds sequence keybinds which can never complete, because an earlier
+    /// part of the sequence is a whole keybind by itself: with `Ctrl+K`
+    /// registered, `Ctrl+K` then `Ctrl+C` never gets past `Ctrl+K`.
+    /// 
+    /// Returns the IDs of the unreachable sequences, in registration order.
+    /// 
+    pub fn unreachable_sequences(&self) -> Vec<&'static str> {
+        fn steps<S>(k : &dyn AvKeybind<S>) -> Vec<&[AvKey]> {
+            std::iter::once(k.keys())
+                .chain(k.then().iter().map(KeyCombo::keys))
+                .collect()
+        }
+
+        self.iter()
+            .filter(|seq| !seq.then().is_empty())
+            .filter(|seq| {
+                let seq_steps = steps(*seq);
+                self.iter()
+                    .filter(|k| k.id() != seq.id())
+                    .map(steps)
+                    .any(|prefix| prefix.len() < seq_steps.len()
+                        && prefix.iter().zip(&seq_steps).all(|(l, r)| same_combo(l, r)))
+            })
+            .map(|seq| seq.id())
+            .collect()
+    }
+
+    ///
+    /// Runs the first registered keybind matching the currently pressed keys.
+    /// 
+    /// Returns whether a keybind was run.
+    /// 
+    /// Keybinds matching the pressed keys exactly are preferred; failing that,
+    /// the first non-[exclusive](AvKeybind::exclusive_modifiers) keybind
+    /// matching with extra modifiers held (see [matches_loose]) is run.
+    /// 
+    /// Keybinds needing modifiers which are not pressed are
+    /// rejected by their [modifier mask](modifier_mask) before
+    /// doing any per-key matching.
+    /// 
+    /// Only plain keybinds run here: [sequences](AvKeybind::then) and keybinds firing
+    /// on [release](AvKeybind::trigger) need the keys followed over time, and keybinds
+    /// [requiring](AvKeybind::requires) capabilities need them provided, so they are
+    /// left to a [Dispatcher](crate::Dispatcher).
+    /// 
+    pub fn dispatch(&self, pressed : &[KeyCode], state : &mut S) -> bool {
+        let mask = pressed_modifier_mask(pressed);
+        let single_shot = |e : &&Entry<S>| e.keybind.then().is_empty()
+            && e.keybind.requires().is_empty()
+            && e.keybind.trigger() == TriggerMode::Press;
+
+        let found = self.keybinds.iter()
+            .filter(single_shot)
+            .filter(|e| modifiers_fit(e.mask, e.either, mask, true))
+            .find_map(|e| matches(e.keybind.keys(), pressed).map(|params| (e, params)))
+            .or_else(|| self.keybinds.iter()
+                .filter(single_shot)
+                .filter(|e| !e.keybind.exclusive_modifiers() && modifiers_fit(e.mask, e.either, mask, false))
+                .find_map(|e| matches_loose(e.keybind.keys(), pressed).map(|params| (e, params)))
+            );
+
+        match found {
+            Some((e, params)) => {
+                e.keybind.run(state, params);
+                true
+            },
+            None => false,
+        }
+    }
+
+    ///
+    /// Finds pairs of keybinds which fire for the same keys,
+    /// e.g. `Ctrl+{d}` and `Ctrl+5`, in registration order.
+    /// 
+    /// Keybinds conflict when some keys [match](matches) both exactly,
+    /// with neither forbidding (`!Shift`) what the other needs.
+    /// 
+    pub fn check_conflicts(&self) -> Vec<Conflict> {
+        let keybinds = self.iter().collect::<Vec<_>>();
+
+        keybinds.iter()
+            .enumerate()
+            .flat_map(|(i, a)| keybinds[i + 1..].iter().map(move |b| (*a, *b)))
+            .filter_map(|(a, b)| {
+                let (a_keys, b_keys) = (a.keys(), b.keys());
+                let pressed = shared_press(a_keys, b_keys)?;
+
+                let fixed = |keys : &[AvKey], k| keys.iter().any(|key| key.key().is_some() && AvKey::Key(k).matches(key));
+                let key = pressed.iter()
+                    .copied()
+                    .find(|k| !(fixed(a_keys, *k) && fixed(b_keys, *k)));
+
+                Some(Conflict {
+                    ids  : [a.id(), b.id()],
+                    keys : [a_keys.to_vec(), b_keys.to_vec()],
+                    key,
+                })
+            })
+            .collect()
+    }
+
+    ///
+    /// Metadata of all registered keybinds, in registration order.
+    /// 
+    pub fn infos(&self) -> impl Iterator<Item = KeybindInfo> + '_ {
+        self.iter().map(|k| k.info())
+    }
+
+    ///
+    /// Counts how many registered keybinds use each physical key,
+    /// e.g. for rendering a heatmap over a keyboard.
+    /// 
+    /// Key parameters count every key they cover (`{d}` counts once for each
+    /// digit key); keys which must [not be held](AvKey::NotHeld) are not counted.
+    /// 
+    pub fn key_usage(&self) -> HashMap<KeyCode, usize> {
+        let mut usage = HashMap::new();
+
+        for keybind in self.iter() {
+            let codes = keybind.keys()
+                .iter()
+                .flat_map(|k| match k {
+                    AvKey::Key(code) | AvKey::EitherSide(code) => std::slice::from_ref(code),
+                    AvKey::Parameter(p) => p.keys(),
+                    AvKey::NotHeld(_) => &[],
+                })
+                .collect::<HashSet<_>>();
+
+            for code in codes {
+                *usage.entry(*code).or_insert(0) += 1;
+            }
+        }
+
+        usage
+    }
+
+    ///
+    /// Exports all keybinds as a `.desktop`-style (INI-like) shortcuts file.
+    /// 
+    /// Each keybind gets its own group, named after its ID,
+    /// with `Name`, `Shortcut`, and `Comment` entries.
+    /// Key parameters are written in their macro form (`{d}`), and
+    /// newlines in descriptions are escaped as `\n`.
+    /// 
+    /// This is an interop format for desktop environments &mdash; it is not
+    /// meant to be read back in.
+    /// 
+    /// ### Example
+    /// ```text
+    /// [Shortcut AvSearch]
+    /// Name=AvSearch
+    /// Shortcut=Win+Space
+    /// Comment=Opens a spotlight-search inspired prompt.
+    /// ```
+    /// 
+    pub fn export_desktop(&self) -> String {
+        self.infos()
+            .map(|info| {
+                format!(
+                    "[Shortcut {id}]\nName={id}\nShortcut={}\nComment={}\n",
+                    format_combo(&info.keys, "+"),
+                    info.description.replace('\n', "\\n"),
+                    id = info.id,
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+///
+/// Finds keys matching both combinations, if any, by trying each
+/// concrete set of keys `a` stands for (see [expand]): both sides
+/// of its either side modifiers, and each way of filling in its key parameters.
+/// 
+fn shared_press(a : &[AvKey], b : &[AvKey]) -> Option<Vec<KeyCode>> {
+    expand(a)
+        .into_iter()
+        .find(|pressed| matches(a, pressed).is_some() && matches(b, pressed).is_some())
+}
+
+///
+/// Whether two combinations have exactly the same keys, in any order.
+/// 
+fn same_combo(l : &[AvKey], r : &[AvKey]) -> bool {
+    KeyCombo::new(l.to_vec()) == KeyCombo::new(r.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::keybind::testing::TestKeybind;
+    use crate::{parse_keybind, AvKey, AvKeyParameter, KeyCombo, Layout, OverrideError, TriggerMode};
+
+    use super::Registry;
+
+    fn av_search() -> TestKeybind {
+        TestKeybind {
+            description : "Opens a spotlight-search inspired prompt.",
+            ..TestKeybind::new("AvSearch", &[AvKey::Key(125), AvKey::Key(57)])
+        }
+    }
+
+    fn switch_tab() -> TestKeybind {
+        TestKeybind {
+            description : "Switch to the `d`-th tab.\nTab 0 is the last tab.",
+            ..TestKeybind::new("SwitchTab", &[AvKey::EitherSide(29), AvKey::Parameter(AvKeyParameter::DigitKey)])
+        }
+    }
+
+    #[test]
+    fn export_desktop() {
+        let mut registry = Registry::new();
+        registry.register(av_search());
+        registry.register(switch_tab());
+
+        let out = registry.export_desktop();
+        let groups = out.split("\n\n").collect::<Vec<_>>();
+
+        assert_eq!(groups.len(), 2);
+        assert!(groups[0].starts_with("[Shortcut AvSearch]\nName=AvSearch\n"));
+        assert!(groups[1].starts_with("[Shortcut SwitchTab]\nName=SwitchTab\n"));
+        assert!(groups[1].contains("Shortcut=Ctrl+{d}\n"));
+        assert!(groups[1].contains("Comment=Switch to the `d`-th tab.\\nTab 0 is the last tab.\n"));
+    }
+
+    #[test]
+    fn lookup_concrete_combo() {
+        let mut registry = Registry::new();
+        registry.register(av_search());
+        registry.register(switch_tab());
+
+        assert_eq!(registry.lookup_combo("Ctrl+5").map(|k| k.id()), Some("SwitchTab"));
+        assert_eq!(registry.lookup_combo("Ctrl+{d}").map(|k| k.id()), Some("SwitchTab"));
+        assert_eq!(registry.lookup_combo("Logo+Space").map(|k| k.id()), Some("AvSearch"));
+        assert!(registry.lookup_combo("Ctrl+A").is_none());
+        assert!(registry.lookup_combo("Ctrl+{f}").is_none());
+    }
+
+    #[test]
+    fn exclusive_modifiers() {
+        let ctrl_a = || TestKeybind::new("SelectAll", &[AvKey::Key(29), AvKey::Key(30)]);
+        let ctrl_shift_a = [29, 42, 30];
+
+        let mut registry = Registry::new();
+        registry.register(ctrl_a());
+        assert!(registry.dispatch(&[29, 30], &mut ()));
+        assert!(registry.dispatch(&ctrl_shift_a, &mut ()));
+
+        let mut registry = Registry::new();
+        registry.register(TestKeybind { exclusive : true, ..ctrl_a() });
+        assert!(registry.dispatch(&[29, 30], &mut ()));
+        assert!(!registry.dispatch(&ctrl_shift_a, &mut ()));
+    }
+
+    #[test]
+    fn dispatch_either_side() {
+        let mut registry = Registry::new();
+        registry.register(TestKeybind::new("Copy", &[AvKey::EitherSide(29), AvKey::Key(46)]));
+        registry.register(TestKeybind::new("Paste", &[AvKey::Key(29), AvKey::Key(47)]));
+
+        assert!(registry.dispatch(&[29, 46], &mut ()));
+        assert!(registry.dispatch(&[97, 46], &mut ()));
+        assert!(registry.dispatch(&[29, 47], &mut ()));
+        assert!(!registry.dispatch(&[97, 47], &mut ()));
+    }
+
+    #[test]
+    fn dispatch_skips_timed_keybinds() {
+        let mut registry = Registry::new();
+        registry.register(TestKeybind {
+            then : vec![KeyCombo::new(vec![AvKey::Key(46)])],
+            ..TestKeybind::recording("Comment", &[AvKey::Key(29), AvKey::Key(37)])
+        });
+        registry.register(TestKeybind {
+            trigger : TriggerMode::Release,
+            ..TestKeybind::recording("PushToTalk", &[AvKey::Key(125), AvKey::Key(47)])
+        });
+        registry.register(TestKeybind {
+            requires : &["screenshot"],
+            ..TestKeybind::recording("Screenshot", &[AvKey::Key(99)])
+        });
+        registry.register(TestKeybind::recording("Kill", &[AvKey::Key(29), AvKey::Key(37)]));
+
+        let mut runs = vec![];
+        assert!(!registry.dispatch(&[125, 47], &mut runs));
+        assert!(!registry.dispatch(&[99], &mut runs));
+        assert!(registry.dispatch(&[29, 37], &mut runs));
+        assert_eq!(runs, [("Kill", vec![])]);
+    }
+
+    #[test]
+    fn unreachable_sequences() {
+        let mut registry = Registry::new();
+        registry.register(TestKeybind::new("CommentLine", &[AvKey::Key(29), AvKey::Key(37)]));
+        registry.register(TestKeybind {
+            then : vec![KeyCombo::new(vec![AvKey::Key(29), AvKey::Key(46)])],
+            ..TestKeybind::new("CommentBlock", &[AvKey::Key(37), AvKey::Key(29)])
+        });
+        registry.register(TestKeybind {
+            then : vec![KeyCombo::new(vec![AvKey::Key(29), AvKey::Key(22)])],
+            ..TestKeybind::new("Uncomment", &[AvKey::Key(29), AvKey::Key(32)])
+        });
+
+        assert_eq!(registry.unreachable_sequences(), ["CommentBlock"]);
+    }
+
+    #[test]
+    fn dispatch_custom_state() {
+        let mut registry = Registry::new();
+        registry.register(TestKeybind::recording("Count", &[AvKey::Key(29), AvKey::Key(46)]));
+
+        let mut runs = vec![];
+        registry.dispatch(&[29, 46], &mut runs);
+        registry.dispatch(&[29, 46], &mut runs);
+        assert_eq!(runs, [("Count", vec![]), ("Count", vec![])]);
+    }
+
+    #[test]
+    fn continuations() {
+        let ctrl_k = [AvKey::Key(29), AvKey::Key(37)];
+        let mut registry = Registry::new();
+        registry.register(TestKeybind {
+            then : vec![KeyCombo::new(vec![AvKey::Key(46)])],
+            ..TestKeybind::new("CommentBlock", &ctrl_k)
+        });
+        registry.register(TestKeybind {
+            then : vec![KeyCombo::new(vec![AvKey::Key(29), AvKey::Key(22)])],
+            ..TestKeybind::new("Uncomment", &ctrl_k)
+        });
+        registry.register(av_search());
+
+        let next = registry.continuations(&[AvKey::Key(37), AvKey::Key(29)])
+            .into_iter()
+            .map(|(key, k)| (key.key(), k.id()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(next, [(Some(46), "CommentBlock"), (Some(22), "Uncomment")]);
+        assert!(registry.continuations(&[AvKey::Key(125), AvKey::Key(57)]).is_empty());
+    }
+
+    #[test]
+    fn conflict_message() {
+        let mut registry = Registry::new();
+        registry.register(switch_tab());
+        registry.register(av_search());
+        registry.register(TestKeybind::new("QuickAction", &[AvKey::EitherSide(29), AvKey::Key(6)]));
+        registry.register(TestKeybind::new("NoShift", &[AvKey::EitherSide(29), AvKey::Key(6), AvKey::NotHeld(42)]));
+
+        let conflicts = registry.check_conflicts();
+        let messages = conflicts.iter().map(|c| c.message()).collect::<Vec<_>>();
+
+        assert_eq!(messages, [
+            "`Ctrl+{d}` (SwitchTab) conflicts with `Ctrl+5` (QuickAction) for key `5`",
+            "`Ctrl+{d}` (SwitchTab) conflicts with `Ctrl+5+!Shift` (NoShift) for key `5`",
+            "`Ctrl+5` (QuickAction) conflicts with `Ctrl+5+!Shift` (NoShift)",
+        ]);
+        assert_eq!(conflicts[0].key, Some(6));
+    }
+
+    #[test]
+    fn conflict_on_right_side() {
+        let mut registry = Registry::new();
+        registry.register(TestKeybind::new("Copy", &[AvKey::EitherSide(29), AvKey::Key(46)]));
+        registry.register(TestKeybind::new("RightCopy", &[AvKey::Key(97), AvKey::Key(46)]));
+
+        let conflicts = registry.check_conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].ids, ["Copy", "RightCopy"]);
+    }
+
+    #[test]
+    fn key_usage() {
+        let mut registry = Registry::new();
+        registry.register(av_search());
+        registry.register(switch_tab());
+        registry.register(TestKeybind::new("CloseTab", &[AvKey::Key(29), AvKey::Key(17)]));
+
+        let usage = registry.key_usage();
+        assert_eq!(usage[&29], 2);  // Ctrl
+        assert_eq!(usage[&4], 1);   // 3, through `{d}`
+        assert_eq!(usage[&125], 1); // Logo
+        assert!(!usage.contains_key(&30));
+    }
+
+    #[cfg(feature = "inventory")]
+    inventory::submit! {
+        crate::KeybindRegistration::new(|| Box::new(av_search()))
+    }
+
+    #[cfg(feature = "inventory")]
+    inventory::submit! {
+        crate::KeybindRegistration::new(|| Box::new(switch_tab()))
+    }
+
+    #[test]
+    #[cfg(feature = "inventory")]
+    fn from_inventory() {
+        let registry = Registry::from_inventory();
+        let mut ids = registry.iter().map(|k| k.id()).collect::<Vec<_>>();
+        ids.sort();
+
+        assert_eq!(ids, ["AvSearch", "SwitchTab"]);
+    }
+
+    #[test]
+    fn keybinds_macro() {
+        // Not `()` state, so these stay out of `from_inventory`.
+        #[crate::AvKeybind(Logo+Space, state = u8)]
+        pub fn AvSearch(_state : &mut u8) {}
+
+        #[crate::AvKeybind(Ctrl+{d}, state = u8)]
+        pub fn SwitchTab(_state : &mut u8, _tab : usize) {}
+
+        #[crate::AvKeybind(A+Ctrl, state = u8)]
+        pub fn SelectAll(_state : &mut u8) {}
+
+        let registry = crate::keybinds! { AvSearch, SwitchTab, SelectAll };
+        let ids = registry.iter().map(|k| k.id()).collect::<Vec<_>>();
+        assert_eq!(ids, ["AvSearch", "SwitchTab", "SelectAll"]);
+    }
+
+    #[test]
+    fn set_override_checks_parameters() {
+        let mut registry = Registry::new();
+        registry.register(TestKeybind::recording("SwitchTab", &[AvKey::Key(29), AvKey::Parameter(AvKeyParameter::DigitKey)]));
+
+        let alt_digit = vec![AvKey::Key(56), AvKey::Parameter(AvKeyParameter::DigitKey)];
+        assert_eq!(registry.set_override("SwitchTab", Some(alt_digit.clone())), Ok(true));
+        assert_eq!(registry.set_override("Nope", Some(alt_digit.clone())), Ok(false));
+
+        let err = registry.set_override("SwitchTab", Some(vec![AvKey::Key(56), AvKey::Key(2)])).unwrap_err();
+        assert_eq!(err, OverrideError::ParameterMismatch { expected : vec![AvKeyParameter::DigitKey], found : vec![] });
+        assert_eq!(registry.get("SwitchTab").unwrap().keys(), alt_digit);
+
+        let err = registry.set_logical_override("SwitchTab", "Alt+'a'", Layout::UsQwerty).unwrap_err();
+        assert!(matches!(err, OverrideError::ParameterMismatch { .. }));
+        assert_eq!(registry.get("SwitchTab").unwrap().keys(), alt_digit);
+
+        // Still dispatches with the previous override.
+        let mut runs = vec![];
+        assert!(registry.dispatch(&[56, 4], &mut runs));
+        assert_eq!(runs, [("SwitchTab", vec![3])]);
+    }
+
+    #[test]
+    fn remap_layout() {
+        let mut registry = Registry::new();
+        registry.register(TestKeybind::new("Save", &[]));
+        registry.register(TestKeybind::new("Close", &[]));
+
+        // `s` is under the QWERTY `;` key on Dvorak.
+        assert!(registry.set_logical_override("Save", "Ctrl+'s'", Layout::UsQwerty).unwrap());
+        registry.set_override("Close", Some(parse_keybind("Ctrl+W").unwrap())).unwrap();
+        assert_eq!(registry.get("Save").unwrap().keys(), [AvKey::EitherSide(29), AvKey::Key(31)]);
+
+        registry.remap_layout(Layout::UsQwerty, Layout::Dvorak);
+        assert_eq!(registry.get("Save").unwrap().keys(), [AvKey::EitherSide(29), AvKey::Key(39)]);
+        assert_eq!(registry.get("Close").unwrap().keys(), [AvKey::EitherSide(29), AvKey::Key(17)]);
+
+        registry.remap_layout(Layout::Dvorak, Layout::UsQwerty);
+        assert_eq!(registry.get("Save").unwrap().keys(), [AvKey::EitherSide(29), AvKey::Key(31)]);
+
+        assert!(!registry.set_logical_override("Nope", "'s'", Layout::UsQwerty).unwrap());
+        assert!(matches!(
+            registry.set_logical_override("Save", "Ctrl+'!'", Layout::UsQwerty),
+            Err(OverrideError::InvalidKeys(_)),
+        ));
+    }
+
+    #[test]
+    fn layout_affected_keys() {
+        let mut registry = Registry::new();
+        registry.register(TestKeybind::new("Save", &[AvKey::Key(29), AvKey::Key(31)]));
+        assert!(registry.layout_affected_keys("Save").is_empty());
+
+        registry.set_logical_override("Save", "Ctrl+Shift+'s'", Layout::UsQwerty).unwrap();
+        assert_eq!(registry.layout_affected_keys("Save"), [AvKey::Key(31)]);
+
+        registry.set_override("Save", None).unwrap();
+        assert!(registry.layout_affected_keys("Save").is_empty());
+        assert!(registry.layout_affected_keys("Nope").is_empty());
+    }
+}
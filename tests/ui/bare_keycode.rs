@@ -0,0 +1,6 @@
+use avkeys::AvKeybind;
+
+#[AvKeybind(Ctrl+200)]
+pub fn BareKeycode(state : &mut ()) {}
+
+fn main() {}
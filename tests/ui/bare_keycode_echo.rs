@@ -0,0 +1,6 @@
+use avkeys::AvKeybind;
+
+#[AvKeybind(Logo + Shift + 100 + !Alt)]
+pub fn Screenshot(state : &mut ()) {}
+
+fn main() {}
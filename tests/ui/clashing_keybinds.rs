@@ -0,0 +1,11 @@
+use avkeys::{keybinds, AvKeybind};
+
+#[AvKeybind(Ctrl+Shift+T)]
+pub fn ReopenTab(_state : &mut ()) {}
+
+#[AvKeybind(Shift+Ctrl+T)]
+pub fn NewTerminal(_state : &mut ()) {}
+
+fn main() {
+    let _ = keybinds! { ReopenTab, NewTerminal };
+}
@@ -0,0 +1,6 @@
+avkeys_macros::keycodes! {
+    Escape      => 1     match [Esc],
+    Grave       => 41    match [Esc],
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+avkeys_macros::keycodes! {
+    Minus       => 12    match ['-'],
+    KeyPadMinus => 74    match ['-'],
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use avkeys::AvKeybind;
+
+#[AvKeybind(Ctrl + Ctrl + A)]
+pub fn SelectAll(state : &mut ()) {}
+
+#[AvKeybind(Ctrl + A + !Ctrl)]
+pub fn SelectNone(state : &mut ()) {}
+
+fn main() {}
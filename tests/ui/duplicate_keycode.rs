@@ -0,0 +1,6 @@
+avkeys_macros::keycodes! {
+    Enter       => 28,
+    KeyPadEnter => 28,
+}
+
+fn main() {}
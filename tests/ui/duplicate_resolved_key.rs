@@ -0,0 +1,18 @@
+use avkeys::AvKeybind;
+
+#[AvKeybind(Esc + Escape)]
+pub fn Quit(_state : &mut ()) {}
+
+#[AvKeybind(Ctrl + A + [30])]
+pub fn SelectAll(_state : &mut ()) {}
+
+#[AvKeybind(Alt + '1' + Digit1)]
+pub fn FirstTab(_state : &mut ()) {}
+
+#[AvKeybind(Alt + Tab + !LeftAlt)]
+pub fn SwitchWindow(_state : &mut ()) {}
+
+#[AvKeybind(Logo + Win + {d})]
+pub fn SwitchDesktop(_state : &mut (), _desktop : usize) {}
+
+fn main() {}
@@ -0,0 +1,7 @@
+avkeys_macros::keycodes! {
+    //!
+    //! No keys here.
+    //!
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use avkeys::AvKeybind;
+
+#[AvKeybind(Ctrl+Shift+{d})]
+pub fn PetKitty(state : &mut (), times : d) {}
+
+fn main() {}
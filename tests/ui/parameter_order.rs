@@ -0,0 +1,10 @@
+use avkeys::AvKeybind;
+
+// Private, so that the macro stops after the key order lint.
+#[AvKeybind({d}+A)]
+fn SwitchTab(state : &mut (), tab : usize) {}
+
+#[AvKeybind({d}+A, any_order)]
+fn SwitchWorkspace(state : &mut (), workspace : usize) {}
+
+fn main() {}
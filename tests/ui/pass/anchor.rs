@@ -0,0 +1,61 @@
+use avkeys::{AvKeybind, Dispatcher, Key};
+
+type Events = Vec<&'static str>;
+
+#[AvKeybind(Alt+Tab, anchor = Alt, state = Events)]
+pub fn SwitchWindow(events : &mut Events) {
+    events.push("next");
+}
+
+#[AvKeybind(Ctrl+Tab, state = Events)]
+pub fn SwitchTab(events : &mut Events) {
+    events.push("tab");
+}
+
+// The anchor may be written differently from the keybind's key.
+#[AvKeybind(Alt+'`', anchor = [56], state = Events)]
+pub fn SwitchWindowGroup(_events : &mut Events) {}
+
+#[AvKeybind(LeftAlt+Escape, anchor = Alt, state = Events)]
+pub fn SwitchWindowBack(_events : &mut Events) {}
+
+#[AvKeybind(Escape+Tab, anchor = Esc, state = Events)]
+pub fn Overview(_events : &mut Events) {}
+
+fn main() {
+    assert_eq!(SwitchWindow::default().anchor_modifier(), Some(Key::LeftAlt.code()));
+    assert_eq!(SwitchWindowGroup::default().anchor_modifier(), Some(Key::LeftAlt.code()));
+    assert_eq!(SwitchWindowBack::default().anchor_modifier(), Some(Key::LeftAlt.code()));
+    assert_eq!(Overview::default().anchor_modifier(), Some(Key::Esc.code()));
+    assert_eq!(SwitchTab::default().anchor_modifier(), None);
+
+    let mut dispatcher = Dispatcher::new();
+    dispatcher.register(SwitchWindow::default());
+    dispatcher.register(SwitchTab::default());
+    dispatcher.on_anchor_release(|id, events : &mut Events| events.push(id));
+
+    let (alt, right_alt, ctrl, tab) = (56, 100, 29, 15);
+    let mut events = vec![];
+
+    // Cycles while Alt stays held, and finishes once it's released.
+    dispatcher.feed(&[alt, tab], &mut events);
+    dispatcher.feed(&[alt], &mut events);
+    dispatcher.feed(&[alt, tab], &mut events);
+    assert_eq!(events, ["next", "next"]);
+    dispatcher.feed(&[], &mut events);
+    assert_eq!(events, ["next", "next", "SwitchWindow"]);
+
+    // Either side of the anchor holds it.
+    events.clear();
+    dispatcher.feed(&[right_alt, tab], &mut events);
+    dispatcher.feed(&[right_alt], &mut events);
+    assert_eq!(events, ["next"]);
+    dispatcher.feed(&[], &mut events);
+    assert_eq!(events, ["next", "SwitchWindow"]);
+
+    // Keybinds without an anchor finish nothing.
+    events.clear();
+    dispatcher.feed(&[ctrl, tab], &mut events);
+    dispatcher.feed(&[], &mut events);
+    assert_eq!(events, ["tab"]);
+}
@@ -0,0 +1,18 @@
+use avkeys::{keyparameters, AvKey, AvKeyParameter, AvKeybind};
+
+keyparameters! {
+    kp => KeyPadDigit [82, 79, 80, 81, 75, 76, 77, 71, 72, 73],
+}
+
+#[AvKeybind(Logo+{kp}, params = [kp])]
+pub fn SwitchWorkspace(_state : &mut (), workspace : usize) {
+    assert_eq!(workspace, 5);
+}
+
+fn main() {
+    assert_eq!(SwitchWorkspace::DEFAULT_KEYS[1], AvKey::Parameter(KeyPadDigit));
+    assert_eq!(SwitchWorkspace::EXPECTED_PARAMS, [KeyPadDigit]);
+    let _ : AvKeyParameter = kp;
+
+    SwitchWorkspace::default().run(&mut (), vec![5]);
+}
@@ -0,0 +1,16 @@
+use avkeys::{parse_keybind, AvKeybind};
+
+#[AvKeybind(Logo + Space)]
+pub fn AvSearch(_state : &mut ()) {}
+
+#[AvKeybind(Ctrl+'+'+[0x6f]+{d}+!Shift, exclusive)]
+pub fn Everything(_state : &mut (), _d : usize) {}
+
+fn main() {
+    assert_eq!(AvSearch::default_combo_string(), "Logo+Space");
+    assert_eq!(Everything::default_combo_string(), "Ctrl+'+'+[0x6f]+{d}+!Shift");
+
+    // Reads back as the same keys.
+    assert_eq!(parse_keybind(&AvSearch::default_combo_string()).unwrap(), AvSearch::DEFAULT_KEYS);
+    assert_eq!(parse_keybind(&Everything::default_combo_string()).unwrap(), Everything::DEFAULT_KEYS);
+}
@@ -0,0 +1,27 @@
+use avkeys::{AvKey, AvKeyParameter, AvKeybind, Registry};
+
+///
+/// Switches to the `d`-th tab.
+///
+#[AvKeybind(Ctrl+Alt+{d}+!Shift)]
+pub fn SwitchTab(_state : &mut (), tab : usize) {
+    assert_eq!(tab, 3);
+}
+
+fn main() {
+    assert_eq!(SwitchTab::DEFAULT_KEYS, [
+        AvKey::EitherSide(29),
+        AvKey::EitherSide(56),
+        AvKey::Parameter(AvKeyParameter::DigitKey),
+        AvKey::NotHeld(42),
+    ]);
+
+    let keybind = SwitchTab::default();
+    assert_eq!(keybind.id(), "SwitchTab");
+    assert_eq!(keybind.description(), "Switches to the `d`-th tab.");
+    assert_eq!(keybind.keys(), SwitchTab::DEFAULT_KEYS);
+
+    let mut registry = Registry::new();
+    registry.register(keybind);
+    assert!(registry.dispatch(&[29, 56, 4], &mut ()));
+}
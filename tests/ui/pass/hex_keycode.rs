@@ -0,0 +1,13 @@
+use avkeys::{AvKey, AvKeybind, Key};
+
+#[AvKeybind(Ctrl+[0x1e])]
+pub fn HexCode(_state : &mut ()) {}
+
+#[AvKeybind(Ctrl+[30])]
+pub fn DecimalCode(_state : &mut ()) {}
+
+fn main() {
+    assert_eq!(HexCode::DEFAULT_KEYS, [AvKey::EitherSide(29), AvKey::Key(30)]);
+    assert_eq!(HexCode::DEFAULT_KEYS, DecimalCode::DEFAULT_KEYS);
+    assert_eq!(HexCode::DEFAULT_KEYS[1], AvKey::Key(Key::A.code()));
+}
@@ -0,0 +1,28 @@
+use std::time::{Duration, Instant};
+
+use avkeys::{AvKeybind, Dispatcher, Key};
+
+type Events = Vec<&'static str>;
+
+#[AvKeybind(Power hold 2s, state = Events)]
+pub fn PowerOff(events : &mut Events) {
+    events.push("off");
+}
+
+fn main() {
+    assert_eq!(PowerOff::default().hold_duration(), Some(Duration::from_secs(2)));
+
+    let mut dispatcher = Dispatcher::new();
+    dispatcher.register(PowerOff::default());
+
+    let power = Key::Power.code();
+    let start = Instant::now();
+    let mut events = vec![];
+
+    dispatcher.feed_at(&[power], &mut events, start);
+    dispatcher.feed_at(&[power], &mut events, start + Duration::from_secs(1));
+    assert!(events.is_empty());
+
+    dispatcher.feed_at(&[power], &mut events, start + Duration::from_secs(2));
+    assert_eq!(events, ["off"]);
+}
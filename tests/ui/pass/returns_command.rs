@@ -0,0 +1,28 @@
+use avkeys::AvKeybind;
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Close,
+    Focus(usize),
+}
+
+///
+/// Focuses the `d`-th workspace, or closes the window for `0`.
+///
+#[AvKeybind(Logo+{d})]
+pub fn FocusWorkspace(_state : &mut (), workspace : usize) -> Command {
+    match workspace {
+        0 => Command::Close,
+        n => Command::Focus(n),
+    }
+}
+
+fn main() {
+    let keybind = FocusWorkspace::default();
+    assert_eq!(keybind.run(&mut (), vec![3]), Command::Focus(3));
+    assert_eq!(keybind.run(&mut (), vec![0]), Command::Close);
+
+    let boxed : Box<dyn AvKeybind<(), Command>> = Box::new(keybind);
+    assert_eq!(boxed.run(&mut (), vec![1]), Command::Focus(1));
+    assert_eq!(boxed.parameter_count(), 1);
+}
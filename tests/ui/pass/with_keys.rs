@@ -0,0 +1,33 @@
+use avkeys::{AvKey, AvKeyParameter, AvKeybind, Key, OverrideError};
+
+#[AvKeybind(Ctrl+{d})]
+pub fn SwitchTab(_state : &mut (), _tab : usize) {}
+
+fn main() {
+    let alt = AvKey::Key(Key::LeftAlt.code());
+    let digit = AvKey::Parameter(AvKeyParameter::DigitKey);
+
+    assert_eq!(SwitchTab::EXPECTED_PARAMS, [AvKeyParameter::DigitKey]);
+
+    // Same key parameters: accepted.
+    assert!(SwitchTab::accepts_override(&[alt, digit]));
+    let keybind = SwitchTab::with_keys(vec![alt, digit]).unwrap();
+    assert_eq!(keybind.keys(), [alt, digit]);
+    assert_eq!(SwitchTab::default().keys(), SwitchTab::DEFAULT_KEYS);
+
+    // The `{d}` is gone: rejected.
+    assert!(!SwitchTab::accepts_override(&[alt, AvKey::Key(2)]));
+    match SwitchTab::with_keys(vec![alt, AvKey::Key(2)]) {
+        Err(OverrideError::ParameterMismatch { expected, found }) => {
+            assert_eq!(expected, [AvKeyParameter::DigitKey]);
+            assert_eq!(found, []);
+        },
+        Ok(_) => panic!("`Alt+2` has no `{{d}}` to pass to SwitchTab"),
+        Err(err) => panic!("Unexpected error: {err}"),
+    }
+
+    // Rejected overrides leave the keys alone.
+    let mut keybind = SwitchTab::default();
+    assert!(keybind.set_keys(vec![alt, AvKey::Key(2)]).is_err());
+    assert_eq!(keybind.keys(), SwitchTab::DEFAULT_KEYS);
+}
@@ -0,0 +1,8 @@
+use avkeys::AvKeybind;
+
+pub struct MyWmState;
+
+#[AvKeybind(Logo+Space, state = MyWmState)]
+pub fn AvSearch(wm : &mut ()) {}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use avkeys::AvKeybind;
+
+#[AvKeybind(Alt+Tab, anchor = Ctrl)]
+pub fn SwitchWindow(_state : &mut ()) {}
+
+#[AvKeybind(RightAlt+{d}, anchor = [29])]
+pub fn SwitchDesktop(_state : &mut (), _desktop : usize) {}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use avkeys::AvKeybind;
+
+#[AvKeybind(Ctrl+{foo})]
+pub fn SwitchTab(_state : &mut (), _tab : usize) {}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use avkeys::AvKeybind;
+
+#[AvKeybind(Logo+V, on = hold)]
+pub fn PushToTalk(state : &mut ()) {}
+
+fn main() {}